@@ -10,25 +10,24 @@ use twisted::cube::cube2::{
     render::print_cube,
     symmetry::fix_dbl_corner,
     Cube2Notation, UrfTurn,
-    Cube2Perm,
 };
 use twisted::move_table::{BasicMoveTable, CompositeMoveTable};
 use twisted::notation::{NotationMove, NotationStr};
 use twisted::prune_table::FullPruneTable;
-use twisted::solver::solve_cube;
+use twisted::solver::{solve_cube, SearchControl};
 
 type Notation = NotationStr<Cube2Notation>;
 
 type FinalMoveTable = CompositeMoveTable<
     'static,
     Corner7Coord,
-    BasicMoveTable<Cube2Perm, CornerOrient7Coord, UrfTurn>,
-    BasicMoveTable<Cube2Perm, CornerPos7Coord, UrfTurn>,
+    BasicMoveTable<CornerOrient7Coord, UrfTurn>,
+    BasicMoveTable<CornerPos7Coord, UrfTurn>,
 >;
 
 lazy_static! {
-    static ref ORIENT_TABLE: BasicMoveTable<Cube2Perm, CornerOrient7Coord, UrfTurn> = BasicMoveTable::create();
-    static ref POS_TABLE: BasicMoveTable<Cube2Perm, CornerPos7Coord, UrfTurn> = BasicMoveTable::create();
+    static ref ORIENT_TABLE: BasicMoveTable<CornerOrient7Coord, UrfTurn> = BasicMoveTable::create();
+    static ref POS_TABLE: BasicMoveTable<CornerPos7Coord, UrfTurn> = BasicMoveTable::create();
     static ref MOVE_TABLE: FinalMoveTable = CompositeMoveTable::new(&*ORIENT_TABLE, &*POS_TABLE);
     static ref PRUNE_TABLE: FullPruneTable<Corner7Coord, UrfTurn> =
         FullPruneTable::create(&*MOVE_TABLE, Corner7Coord::default());
@@ -39,8 +38,14 @@ fn do_solve(notation: Notation) {
     print_cube(&perm);
 
     let (_sym, perm) = fix_dbl_corner(&perm);
-    match solve_cube(&*MOVE_TABLE, &*PRUNE_TABLE, &perm, Corner7Coord::default()) {
-        Some(sol) => {
+    match solve_cube(
+        &*MOVE_TABLE,
+        &*PRUNE_TABLE,
+        &perm,
+        Corner7Coord::default(),
+        &SearchControl::default(),
+    ) {
+        Ok(sol) => {
             let sol_notation = Notation::from(
                 sol.iter()
                     .map(|m| NotationMove::<Cube2Notation>::from(*m))
@@ -48,8 +53,8 @@ fn do_solve(notation: Notation) {
             );
             println!("Solution: {}", sol_notation);
         }
-        None => {
-            println!("No solution found");
+        Err(aborted) => {
+            println!("No solution found: {}", aborted);
         }
     }
 }
@@ -84,8 +89,8 @@ fn main() {
 
                 match input_buf.parse() {
                     Ok(notation) => do_solve(notation),
-                    Err(_) => {
-                        println!("Invalid cube notation");
+                    Err(err) => {
+                        println!("Invalid cube notation: {}", err);
                     }
                 }
             }
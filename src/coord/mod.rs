@@ -47,6 +47,12 @@ pub trait Coord<P>: EnumIndex
 where
     P: PuzzlePerm,
 {
+    /// Name identifying the coordinate in saved tables.
+    ///
+    /// This should stay the same when the type is renamed or moved, and only change when the
+    /// values of the coordinate do.
+    const NAME: &'static str;
+
     fn from_perm(perm: &P) -> Self;
 
     fn into_perm(self) -> P;
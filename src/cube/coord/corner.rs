@@ -13,17 +13,13 @@ make_newtype_enum_index! {
     /// be determined from the others.
     ///
     /// There are 3^6 = 2187 values.
+    #[derive(Default)]
     pub struct CornerOrientCoord(u16): 2187;
 }
 
-impl Default for CornerOrientCoord {
-    fn default() -> Self {
-        // 0 means all oriented
-        Self(0)
-    }
-}
-
 impl Coord<CornerPerm> for CornerOrientCoord {
+    const NAME: &'static str = "CornerOrientCoord";
+
     fn from_perm(perm: &CornerPerm) -> Self {
         let c = parity_coord::calculate_coord(CornerPos::iter().map(|p| perm[p].orient));
         CornerOrientCoord(c)
@@ -53,17 +49,13 @@ make_newtype_enum_index! {
     /// it to go.
     ///
     /// There are 8! = 40320 values.
+    #[derive(Default)]
     pub struct CornerPosCoord(u16): 40320;
 }
 
-impl Default for CornerPosCoord {
-    fn default() -> Self {
-        // 0 means all corners are in order
-        Self(0)
-    }
-}
-
 impl Coord<CornerPerm> for CornerPosCoord {
+    const NAME: &'static str = "CornerPosCoord";
+
     fn from_perm(perm: &CornerPerm) -> Self {
         let c =
             perm_coord::calculate_coord(CornerPos::iter(), CornerPos::iter().map(|p| perm[p].pos));
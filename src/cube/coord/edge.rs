@@ -12,17 +12,13 @@ make_newtype_enum_index! {
     /// orientation can be determined from the others.
     ///
     /// There are 2^11 = 2048 values.
+    #[derive(Default)]
     pub struct EdgeOrientCoord(u16): 2048;
 }
 
-impl Default for EdgeOrientCoord {
-    fn default() -> Self {
-        // 0 means all oriented
-        Self(0)
-    }
-}
-
 impl Coord<EdgePerm> for EdgeOrientCoord {
+    const NAME: &'static str = "EdgeOrientCoord";
+
     fn from_perm(perm: &EdgePerm) -> Self {
         let c = parity_coord::calculate_coord(EdgePos::iter().map(|p| perm[p].orient));
         EdgeOrientCoord(c)
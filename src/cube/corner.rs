@@ -69,7 +69,9 @@ impl CornerPos {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, EnumCount, EnumIter, FromPrimitive)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, EnumCount, EnumIter, FromPrimitive,
+)]
 pub enum CornerOrient {
     Oriented = 0,
     Clockwise = 1,
@@ -156,15 +158,36 @@ impl Corner {
 /// goes to the indexed position. As said above, the returned value is the cubie
 /// which _replaces_ the queried position, not the cubie to which the queried
 /// position _replaces_.
+///
+/// Reflections are represented by setting a "mirrored" flag. The orientations of a mirrored
+/// permutation are measured in the mirrored frame, so they are subtracted instead of added when a
+/// permutation is sequenced after it.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CornerPerm {
     // TODO Refactor so that this can be private (it's used in coordinate calculations)
     pub(crate) cubies: [Corner; 8],
+    pub(crate) mirrored: bool,
 }
 
 impl CornerPerm {
     pub const fn new(cubies: [Corner; 8]) -> Self {
-        Self { cubies }
+        Self {
+            cubies,
+            mirrored: false,
+        }
+    }
+
+    /// Creates a permutation which includes a reflection.
+    pub const fn new_mirrored(cubies: [Corner; 8]) -> Self {
+        Self {
+            cubies,
+            mirrored: true,
+        }
+    }
+
+    /// Whether the permutation includes a reflection.
+    pub const fn is_mirrored(&self) -> bool {
+        self.mirrored
     }
 
     /// Creates an empty (but invalid) CornerPerm.
@@ -172,9 +195,7 @@ impl CornerPerm {
     /// This should only be used when constructing new permutations, never
     /// directly, since it is not a valid permutation.
     fn empty() -> Self {
-        CornerPerm {
-            cubies: [Corner::new(CornerPos::URF, CornerOrient::Oriented); 8],
-        }
+        CornerPerm::new([Corner::new(CornerPos::URF, CornerOrient::Oriented); 8])
     }
 
     /// Gets the face at a particular facelet.
@@ -219,18 +240,16 @@ impl PuzzlePerm for CornerPerm {
         use CornerOrient::*;
         use CornerPos::*;
 
-        const IDENTITY: CornerPerm = CornerPerm {
-            cubies: [
-                Corner::new(ULB, Oriented),
-                Corner::new(UBR, Oriented),
-                Corner::new(URF, Oriented),
-                Corner::new(UFL, Oriented),
-                Corner::new(DLF, Oriented),
-                Corner::new(DFR, Oriented),
-                Corner::new(DRB, Oriented),
-                Corner::new(DBL, Oriented),
-            ],
-        };
+        const IDENTITY: CornerPerm = CornerPerm::new([
+            Corner::new(ULB, Oriented),
+            Corner::new(UBR, Oriented),
+            Corner::new(URF, Oriented),
+            Corner::new(UFL, Oriented),
+            Corner::new(DLF, Oriented),
+            Corner::new(DFR, Oriented),
+            Corner::new(DRB, Oriented),
+            Corner::new(DBL, Oriented),
+        ]);
 
         &IDENTITY
     }
@@ -239,9 +258,16 @@ impl PuzzlePerm for CornerPerm {
         let mut res = CornerPerm::empty();
         for pos in CornerPos::iter() {
             let final_src = self[other[pos].pos];
-            res[pos] = Corner::new(final_src.pos, other[pos].orient + final_src.orient);
+            // Twists applied after a reflection are reversed relative to the mirrored frame
+            let orient = if self.mirrored {
+                final_src.orient - other[pos].orient
+            } else {
+                final_src.orient + other[pos].orient
+            };
+            res[pos] = Corner::new(final_src.pos, orient);
         }
 
+        res.mirrored = self.mirrored != other.mirrored;
         res
     }
 
@@ -249,9 +275,15 @@ impl PuzzlePerm for CornerPerm {
         let mut res = CornerPerm::empty();
         for pos in CornerPos::iter() {
             let dst_corner = self[pos];
-            res[dst_corner.pos] = Corner::new(pos, -dst_corner.orient);
+            let orient = if self.mirrored {
+                dst_corner.orient
+            } else {
+                -dst_corner.orient
+            };
+            res[dst_corner.pos] = Corner::new(pos, orient);
         }
 
+        res.mirrored = self.mirrored;
         res
     }
 }
@@ -11,22 +11,17 @@ make_newtype_enum_index! {
     /// the others.
     ///
     /// Ranges from 0 to 728 (3^6 - 1).
+    #[derive(Default)]
     pub struct CornerOrient7Coord(u16): 729;
 }
 
-impl Default for CornerOrient7Coord {
-    fn default() -> Self {
-        // 0 means all oriented
-        Self(0)
-    }
-}
-
-fn important_corners() -> impl Iterator<Item = CornerPos> + DoubleEndedIterator + ExactSizeIterator
-{
+fn important_corners() -> impl DoubleEndedIterator<Item = CornerPos> + ExactSizeIterator {
     CornerPos::iter().take(7)
 }
 
 impl Coord<CornerPerm> for CornerOrient7Coord {
+    const NAME: &'static str = "CornerOrient7Coord";
+
     fn from_perm(perm: &CornerPerm) -> Self {
         let c = parity_coord::calculate_coord(important_corners().map(|p| perm[p].orient));
         CornerOrient7Coord(c)
@@ -57,17 +52,13 @@ make_newtype_enum_index! {
     /// go.
     ///
     /// Ranges from 0 to 5039 (7! - 1).
+    #[derive(Default)]
     pub struct CornerPos7Coord(u16): 5040;
 }
 
-impl Default for CornerPos7Coord {
-    fn default() -> Self {
-        // 0 means all corners are in order
-        Self(0)
-    }
-}
-
 impl Coord<CornerPerm> for CornerPos7Coord {
+    const NAME: &'static str = "CornerPos7Coord";
+
     fn from_perm(perm: &CornerPerm) -> Self {
         let c = perm_coord::calculate_coord(
             important_corners(),
@@ -94,6 +85,8 @@ make_composite_coord! {
 }
 
 impl Coord<CornerPerm> for Corner7Coord {
+    const NAME: &'static str = "Corner7Coord";
+
     fn from_perm(perm: &CornerPerm) -> Self {
         Corner7Coord::new(
             CornerOrient7Coord::from_perm(perm),
@@ -42,6 +42,8 @@ impl EnumIndex for UrfTurn {
 impl PuzzleMove for UrfTurn {
     type Puzzle = CornerPerm;
 
+    const NAME: &'static str = "UrfTurn";
+
     fn permutation(&self) -> &CornerPerm {
         use primitives::*;
         use UrfTurn::*;
@@ -71,11 +73,17 @@ impl PuzzleMove for UrfTurn {
         }
 
         use UrfTurn::*;
-        match (a, b) {
-            (U, U2) | (U, UP) | (U2, UP) => true,
-            (R, R2) | (R, RP) | (R2, RP) => true,
-            (F, F2) | (F, FP) | (F2, FP) => true,
-            _ => false,
-        }
+        matches!(
+            (a, b),
+            (U, U2)
+                | (U, UP)
+                | (U2, UP)
+                | (R, R2)
+                | (R, RP)
+                | (R2, RP)
+                | (F, F2)
+                | (F, FP)
+                | (F2, FP)
+        )
     }
 }
@@ -1,11 +1,12 @@
 //! 2x2x2 cube puzzle notation primitive
 
+use std::fmt;
 use std::str::FromStr;
 
 use super::{primitives, CornerPerm, UrfTurn};
-use crate::notation::{NotationPrim, NotationMove};
+use crate::notation::{NotationError, NotationErrorReason, NotationMove, NotationPrim};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Cube2Notation {
     U,
     R,
@@ -18,9 +19,9 @@ pub enum Cube2Notation {
 // TODO Make macro for implementing all this
 
 impl FromStr for Cube2Notation {
-    type Err = ();
+    type Err = NotationError;
 
-    fn from_str(s: &str) -> Result<Self, ()> {
+    fn from_str(s: &str) -> Result<Self, NotationError> {
         use Cube2Notation::*;
         match s {
             "U" => Ok(U),
@@ -29,23 +30,27 @@ impl FromStr for Cube2Notation {
             "D" => Ok(D),
             "L" => Ok(L),
             "B" => Ok(B),
-            _ => Err(()),
+            _ => Err(NotationError::new(
+                s,
+                0,
+                s.len(),
+                NotationErrorReason::UnknownPrim,
+            )),
         }
     }
 }
 
-impl ToString for Cube2Notation {
-    fn to_string(&self) -> String {
+impl fmt::Display for Cube2Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use Cube2Notation::*;
-        match self {
+        f.write_str(match self {
             U => "U",
             R => "R",
             F => "F",
             D => "D",
             L => "L",
             B => "B",
-        }
-        .to_string()
+        })
     }
 }
 
@@ -7,83 +7,71 @@ use CornerPos::*;
 
 // Primitive moves
 
-const U: CornerPerm = CornerPerm {
-    cubies: [
-        Corner::new(UFL, Oriented),
-        Corner::new(ULB, Oriented),
-        Corner::new(UBR, Oriented),
-        Corner::new(URF, Oriented),
-        Corner::new(DLF, Oriented),
-        Corner::new(DFR, Oriented),
-        Corner::new(DRB, Oriented),
-        Corner::new(DBL, Oriented),
-    ],
-};
-
-const R: CornerPerm = CornerPerm {
-    cubies: [
-        Corner::new(ULB, Oriented),
-        Corner::new(URF, Clockwise),
-        Corner::new(DFR, AntiClockwise),
-        Corner::new(UFL, Oriented),
-        Corner::new(DLF, Oriented),
-        Corner::new(DRB, Clockwise),
-        Corner::new(UBR, AntiClockwise),
-        Corner::new(DBL, Oriented),
-    ],
-};
-
-const F: CornerPerm = CornerPerm {
-    cubies: [
-        Corner::new(ULB, Oriented),
-        Corner::new(UBR, Oriented),
-        Corner::new(UFL, Clockwise),
-        Corner::new(DLF, AntiClockwise),
-        Corner::new(DFR, Clockwise),
-        Corner::new(URF, AntiClockwise),
-        Corner::new(DRB, Oriented),
-        Corner::new(DBL, Oriented),
-    ],
-};
-
-const D: CornerPerm = CornerPerm {
-    cubies: [
-        Corner::new(ULB, Oriented),
-        Corner::new(UBR, Oriented),
-        Corner::new(URF, Oriented),
-        Corner::new(UFL, Oriented),
-        Corner::new(DBL, Oriented),
-        Corner::new(DLF, Oriented),
-        Corner::new(DFR, Oriented),
-        Corner::new(DRB, Oriented),
-    ],
-};
-
-const L: CornerPerm = CornerPerm {
-    cubies: [
-        Corner::new(DBL, AntiClockwise),
-        Corner::new(UBR, Oriented),
-        Corner::new(URF, Oriented),
-        Corner::new(ULB, Clockwise),
-        Corner::new(UFL, AntiClockwise),
-        Corner::new(DFR, Oriented),
-        Corner::new(DRB, Oriented),
-        Corner::new(DLF, Clockwise),
-    ],
-};
-
-const B: CornerPerm = CornerPerm {
-    cubies: [
-        Corner::new(UBR, Clockwise),
-        Corner::new(DRB, AntiClockwise),
-        Corner::new(URF, Oriented),
-        Corner::new(UFL, Oriented),
-        Corner::new(DLF, Oriented),
-        Corner::new(DFR, Oriented),
-        Corner::new(DBL, Clockwise),
-        Corner::new(ULB, AntiClockwise),
-    ],
-};
+const U: CornerPerm = CornerPerm::new([
+    Corner::new(UFL, Oriented),
+    Corner::new(ULB, Oriented),
+    Corner::new(UBR, Oriented),
+    Corner::new(URF, Oriented),
+    Corner::new(DLF, Oriented),
+    Corner::new(DFR, Oriented),
+    Corner::new(DRB, Oriented),
+    Corner::new(DBL, Oriented),
+]);
+
+const R: CornerPerm = CornerPerm::new([
+    Corner::new(ULB, Oriented),
+    Corner::new(URF, Clockwise),
+    Corner::new(DFR, AntiClockwise),
+    Corner::new(UFL, Oriented),
+    Corner::new(DLF, Oriented),
+    Corner::new(DRB, Clockwise),
+    Corner::new(UBR, AntiClockwise),
+    Corner::new(DBL, Oriented),
+]);
+
+const F: CornerPerm = CornerPerm::new([
+    Corner::new(ULB, Oriented),
+    Corner::new(UBR, Oriented),
+    Corner::new(UFL, Clockwise),
+    Corner::new(DLF, AntiClockwise),
+    Corner::new(DFR, Clockwise),
+    Corner::new(URF, AntiClockwise),
+    Corner::new(DRB, Oriented),
+    Corner::new(DBL, Oriented),
+]);
+
+const D: CornerPerm = CornerPerm::new([
+    Corner::new(ULB, Oriented),
+    Corner::new(UBR, Oriented),
+    Corner::new(URF, Oriented),
+    Corner::new(UFL, Oriented),
+    Corner::new(DBL, Oriented),
+    Corner::new(DLF, Oriented),
+    Corner::new(DFR, Oriented),
+    Corner::new(DRB, Oriented),
+]);
+
+const L: CornerPerm = CornerPerm::new([
+    Corner::new(DBL, AntiClockwise),
+    Corner::new(UBR, Oriented),
+    Corner::new(URF, Oriented),
+    Corner::new(ULB, Clockwise),
+    Corner::new(UFL, AntiClockwise),
+    Corner::new(DFR, Oriented),
+    Corner::new(DRB, Oriented),
+    Corner::new(DLF, Clockwise),
+]);
+
+const B: CornerPerm = CornerPerm::new([
+    Corner::new(UBR, Clockwise),
+    Corner::new(DRB, AntiClockwise),
+    Corner::new(URF, Oriented),
+    Corner::new(UFL, Oriented),
+    Corner::new(DLF, Oriented),
+    Corner::new(DFR, Oriented),
+    Corner::new(DBL, Clockwise),
+    Corner::new(ULB, AntiClockwise),
+]);
 
 // Accessor functions for the primitives and their derivatives
 
@@ -5,6 +5,9 @@ use crate::puzzle::PuzzlePerm;
 
 /// A symmetry permutation, excluding reflections.
 ///
+/// Only rotations are included. A reflection reverses the direction of corner twists, so it is
+/// stored as a mirrored `CornerPerm` instead (see `m_reflection`).
+///
 /// This is built from 3 components:
 ///
 /// - `urf_rot`: 120 degree clockwise rotation about the axis through URF - DLB
@@ -13,20 +16,14 @@ use crate::puzzle::PuzzlePerm;
 ///
 /// Rotations are applied in the order listed. In all, there are 24 symmetries (3 for URF, 4 for y,
 /// and 2 for x2).
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Symmetry(u8);
 
-impl Default for Symmetry {
-    fn default() -> Self {
-        Symmetry(0)
-    }
-}
-
 impl Symmetry {
     pub const COUNT: usize = 24;
 
     pub fn all() -> impl Iterator<Item = Self> {
-        (0..Symmetry::COUNT).into_iter().map(|n| Symmetry(n as u8))
+        (0..Symmetry::COUNT).map(|n| Symmetry(n as u8))
     }
 
     pub fn new(urf_rot: u8, y_rot: u8, x2_rot: u8) -> Self {
@@ -83,6 +80,13 @@ impl Symmetry {
     }
 }
 
+/// Reflection through the M slice, which swaps the L and R faces.
+///
+/// Reflections reverse the direction of turns, so conjugating `R` by this gives `L'`.
+pub fn m_reflection() -> &'static CornerPerm {
+    &permutations::M_REFL
+}
+
 mod permutations {
     use super::*;
 
@@ -123,6 +127,17 @@ mod permutations {
         Corner::new(UBR, Oriented),
     ]);
 
+    pub const M_REFL: CornerPerm = CornerPerm::new_mirrored([
+        Corner::new(UBR, Oriented),
+        Corner::new(ULB, Oriented),
+        Corner::new(UFL, Oriented),
+        Corner::new(URF, Oriented),
+        Corner::new(DFR, Oriented),
+        Corner::new(DLF, Oriented),
+        Corner::new(DBL, Oriented),
+        Corner::new(DRB, Oriented),
+    ]);
+
     lazy_static! {
         pub static ref SYMMETRY_PERMS: [CornerPerm; Symmetry::COUNT] = {
             let mut perms: [CornerPerm; Symmetry::COUNT] = Default::default();
@@ -165,6 +180,39 @@ mod test {
 
     use super::super::primitives;
 
+    fn mirror(perm: &CornerPerm) -> CornerPerm {
+        let refl = m_reflection();
+        refl.sequence(perm).sequence(&refl.invert())
+    }
+
+    #[test]
+    fn m_reflection_is_an_involution() {
+        assert!(m_reflection().is_mirrored());
+        assert!(m_reflection().sequence(m_reflection()).is_identity());
+        assert_eq!(&m_reflection().invert(), m_reflection());
+    }
+
+    #[test]
+    fn m_reflection_turns_r_into_l_prime() {
+        use primitives::*;
+        assert_eq!(&mirror(r()), l_prime());
+        assert_eq!(&mirror(l()), r_prime());
+        assert_eq!(&mirror(u()), u_prime());
+        assert_eq!(&mirror(d()), d_prime());
+        assert_eq!(&mirror(f()), f_prime());
+        assert_eq!(&mirror(b()), b_prime());
+    }
+
+    #[test]
+    fn m_reflection_mirrors_sequences() {
+        use primitives::*;
+        // Each move is mirrored separately, so the corner twists have to cancel out correctly
+        let perm = r().sequence(u()).sequence(f_prime());
+        let mirrored = l_prime().sequence(u_prime()).sequence(f());
+        assert_eq!(mirror(&perm), mirrored);
+        assert!(!mirror(&perm).is_mirrored());
+    }
+
     #[test]
     fn fix_dbl_of_identity_is_identity() {
         assert_eq!(
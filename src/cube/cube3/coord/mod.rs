@@ -1,6 +1,8 @@
+pub mod optimal;
 pub mod phase1;
 pub mod phase2;
 pub mod util;
 
+pub use optimal::*;
 pub use phase1::*;
 pub use phase2::*;
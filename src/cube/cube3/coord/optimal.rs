@@ -0,0 +1,42 @@
+use crate::coord::Coord;
+use crate::cube::corner::CornerPos;
+use crate::util::IntoEnumIterator;
+
+use super::super::Cube3Perm;
+use super::{CornerOrientCoord, CornerPosCoord};
+
+make_composite_coord! {
+    /// Positions and orientations of all corners.
+    ///
+    /// This is used to build the corner pruning table for the optimal solver.
+    ///
+    /// There are 8! * 3^7 = 88,179,840 values.
+    #[derive(Default)]
+    pub struct CornerPosAndOrientCoord<Cube3Perm>(u32) {
+        pos: CornerPosCoord,
+        orient: CornerOrientCoord,
+    }
+}
+
+impl Coord<Cube3Perm> for CornerPosAndOrientCoord {
+    const NAME: &'static str = "CornerPosAndOrientCoord";
+
+    fn from_perm(perm: &Cube3Perm) -> Self {
+        Self::new(
+            CornerPosCoord::from_perm(perm),
+            CornerOrientCoord::from_perm(perm),
+        )
+    }
+
+    fn into_perm(self) -> Cube3Perm {
+        let mut perm: Cube3Perm = self.pos().into_perm();
+        let orient_perm: Cube3Perm = self.orient().into_perm();
+
+        // Apply the orientations to the positioned corners
+        for p in CornerPos::iter() {
+            perm.corners_mut()[p].orient = orient_perm.corners()[p].orient;
+        }
+
+        perm
+    }
+}
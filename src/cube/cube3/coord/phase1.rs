@@ -5,12 +5,15 @@ use crate::cube::corner::CornerPos;
 use crate::cube::edge::{EdgePerm, EdgePos};
 use crate::util::{rotate_left, EnumIndex, IntoEnumIterator};
 
+use super::super::symmetry::SymmetryE;
 use super::super::Cube3Perm;
 use super::util::in_e_slice;
 
 pub use crate::cube::coord::{corner::CornerOrientCoord, edge::EdgeOrientCoord};
 
 impl Coord<Cube3Perm> for CornerOrientCoord {
+    const NAME: &'static str = "CornerOrientCoord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::from_perm(perm.corners())
     }
@@ -21,6 +24,8 @@ impl Coord<Cube3Perm> for CornerOrientCoord {
 }
 
 impl Coord<Cube3Perm> for EdgeOrientCoord {
+    const NAME: &'static str = "EdgeOrientCoord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::from_perm(perm.edges())
     }
@@ -36,17 +41,13 @@ make_newtype_enum_index! {
     /// This coordinate is 0 when all E slice edges are in the E slice.
     ///
     /// There are C(12, 4) = 495 values, where C(n, k) is the binomial coefficient.
+    #[derive(Default)]
     pub struct EEdgePosCoord(u16): 495;
 }
 
-impl Default for EEdgePosCoord {
-    fn default() -> Self {
-        // 0 means all edges are in the E slice
-        Self(0)
-    }
-}
-
 impl Coord<EdgePerm> for EEdgePosCoord {
+    const NAME: &'static str = "EEdgePosCoord";
+
     fn from_perm(perm: &EdgePerm) -> Self {
         let mut c = 0;
         // Number of E slice edges seen
@@ -92,6 +93,8 @@ impl Coord<EdgePerm> for EEdgePosCoord {
 }
 
 impl Coord<Cube3Perm> for EEdgePosCoord {
+    const NAME: &'static str = "EEdgePosCoord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::from_perm(perm.edges())
     }
@@ -110,6 +113,8 @@ make_composite_coord! {
 }
 
 impl Coord<EdgePerm> for ESliceAndEOCoord {
+    const NAME: &'static str = "ESliceAndEOCoord";
+
     fn from_perm(perm: &EdgePerm) -> Self {
         Self::new(
             EEdgePosCoord::from_perm(perm),
@@ -131,6 +136,8 @@ impl Coord<EdgePerm> for ESliceAndEOCoord {
 }
 
 impl Coord<Cube3Perm> for ESliceAndEOCoord {
+    const NAME: &'static str = "ESliceAndEOCoord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::from_perm(perm.edges())
     }
@@ -140,19 +147,35 @@ impl Coord<Cube3Perm> for ESliceAndEOCoord {
     }
 }
 
+make_symmetry_coord! {
+    /// `ESliceAndEOCoord` (also known as the "flip-UDslice" coordinate) reduced by the symmetries
+    /// which preserve the E slice.
+    ///
+    /// There are 64,430 equivalence classes.
+    pub struct ESliceAndEOSymCoord<SymmetryE, ESliceAndEOCoord>(ESliceAndEOClass(u32)): 64_430;
+}
+
 make_composite_coord! {
+    /// Coordinate for phase 1.
+    ///
+    /// The edges come first so that the phase 1 pruning table can be reduced by symmetry (see
+    /// `SymPruneTable`).
+    ///
+    /// There are 495 * 2^11 * 3^7 = 2,217,093,120 values.
     #[derive(Default)]
     pub struct Phase1Coord<Cube3Perm>(u32) {
-        corners: CornerOrientCoord,
         edges: ESliceAndEOCoord,
+        corners: CornerOrientCoord,
     }
 }
 
 impl Coord<Cube3Perm> for Phase1Coord {
+    const NAME: &'static str = "Phase1Coord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::new(
-            CornerOrientCoord::from_perm(perm),
             ESliceAndEOCoord::from_perm(perm),
+            CornerOrientCoord::from_perm(perm),
         )
     }
 
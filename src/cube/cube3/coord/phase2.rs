@@ -3,12 +3,15 @@ use crate::cube::corner::CornerPos;
 use crate::cube::edge::EdgePerm;
 use crate::util::{EnumIndex, IntoEnumIterator};
 
+use super::super::symmetry::SymmetryE;
 use super::super::Cube3Perm;
 use super::util::{e_slice_edges, in_e_slice, ud_edges};
 
 pub use crate::cube::coord::corner::CornerPosCoord;
 
 impl Coord<Cube3Perm> for CornerPosCoord {
+    const NAME: &'static str = "CornerPosCoord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::from_perm(perm.corners())
     }
@@ -24,16 +27,13 @@ make_newtype_enum_index! {
     /// Note: This coordinate is only valid if all of the E edges are in the E slice.
     ///
     /// There are 4! = 24 values.
+    #[derive(Default)]
     pub struct ESliceEdgePosCoord(u8): 24;
 }
 
-impl Default for ESliceEdgePosCoord {
-    fn default() -> Self {
-        Self(0)
-    }
-}
-
 impl Coord<EdgePerm> for ESliceEdgePosCoord {
+    const NAME: &'static str = "ESliceEdgePosCoord";
+
     fn from_perm(perm: &EdgePerm) -> Self {
         let c = perm_coord::calculate_coord(
             e_slice_edges(),
@@ -65,6 +65,8 @@ impl Coord<EdgePerm> for ESliceEdgePosCoord {
 }
 
 impl Coord<Cube3Perm> for ESliceEdgePosCoord {
+    const NAME: &'static str = "ESliceEdgePosCoord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::from_perm(perm.edges())
     }
@@ -84,17 +86,13 @@ make_newtype_enum_index! {
     /// slice edges are in the E slice).
     ///
     /// There are 8! = 40,320 values.
+    #[derive(Default)]
     pub struct UdEdgePosCoord(u16): 40_320;
 }
 
-impl Default for UdEdgePosCoord {
-    fn default() -> Self {
-        // 0 means all edges are in order
-        Self(0)
-    }
-}
-
 impl Coord<EdgePerm> for UdEdgePosCoord {
+    const NAME: &'static str = "UdEdgePosCoord";
+
     fn from_perm(perm: &EdgePerm) -> Self {
         let c = perm_coord::calculate_coord(ud_edges(), ud_edges().map(|p| perm[p].pos));
 
@@ -110,6 +108,8 @@ impl Coord<EdgePerm> for UdEdgePosCoord {
 }
 
 impl Coord<Cube3Perm> for UdEdgePosCoord {
+    const NAME: &'static str = "UdEdgePosCoord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::from_perm(perm.edges())
     }
@@ -128,19 +128,16 @@ make_composite_coord! {
     /// and hence the U and D edges are in the U and D slices).
     ///
     /// There are 8! * 8! = 1,625,702,400 values.
+    #[derive(Default)]
     pub struct Phase2MinusECoord<Cube3Perm>(u32) {
         corners: CornerPosCoord,
         edges: UdEdgePosCoord,
     }
 }
 
-impl Default for Phase2MinusECoord {
-    fn default() -> Self {
-        Self(0)
-    }
-}
-
 impl Coord<Cube3Perm> for Phase2MinusECoord {
+    const NAME: &'static str = "Phase2MinusECoord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::new(
             CornerPosCoord::from_perm(perm),
@@ -161,6 +158,41 @@ impl Coord<Cube3Perm> for Phase2MinusECoord {
     }
 }
 
+make_symmetry_coord! {
+    /// `CornerPosCoord` reduced by the symmetries which preserve the E slice.
+    ///
+    /// There are 2,768 equivalence classes.
+    pub struct CornerPosSymCoord<SymmetryE, CornerPosCoord>(CornerPosClass(u16)): 2_768;
+}
+
+make_composite_coord! {
+    /// Positions of the corners and E slice edges.
+    ///
+    /// This is used to build the phase 2 pruning table.
+    ///
+    /// There are 8! * 4! = 967,680 values.
+    #[derive(Default)]
+    pub struct CornerPosAndESliceCoord<Cube3Perm>(u32) {
+        corners: CornerPosCoord,
+        e_edges: ESliceEdgePosCoord,
+    }
+}
+
+impl Coord<Cube3Perm> for CornerPosAndESliceCoord {
+    const NAME: &'static str = "CornerPosAndESliceCoord";
+
+    fn from_perm(perm: &Cube3Perm) -> Self {
+        Self::new(
+            CornerPosCoord::from_perm(perm),
+            ESliceEdgePosCoord::from_perm(perm),
+        )
+    }
+
+    fn into_perm(self) -> Cube3Perm {
+        Cube3Perm::new(self.corners().into_perm(), self.e_edges().into_perm())
+    }
+}
+
 make_composite_coord! {
     /// Coordinate for phase 2.
     ///
@@ -168,19 +200,16 @@ make_composite_coord! {
     /// and hence the U and D edges are in the U and D slices).
     ///
     /// There are 8! * 8! * 4! = 39,016,857,600 values.
+    #[derive(Default)]
     pub struct Phase2Coord<Cube3Perm>(u64) {
         ud_cubies: Phase2MinusECoord,
         e_edges: ESliceEdgePosCoord,
     }
 }
 
-impl Default for Phase2Coord {
-    fn default() -> Self {
-        Self(0)
-    }
-}
-
 impl Coord<Cube3Perm> for Phase2Coord {
+    const NAME: &'static str = "Phase2Coord";
+
     fn from_perm(perm: &Cube3Perm) -> Self {
         Self::new(
             Phase2MinusECoord::from_perm(perm),
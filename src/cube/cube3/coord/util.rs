@@ -10,12 +10,12 @@ pub fn in_e_slice(pos: EdgePos) -> bool {
     pos > EdgePos::DL
 }
 
-pub fn ud_edges() -> impl Iterator<Item = EdgePos> + DoubleEndedIterator + ExactSizeIterator {
+pub fn ud_edges() -> impl DoubleEndedIterator<Item = EdgePos> + ExactSizeIterator {
     // EdgePos is organized to have the UD edges first
     EdgePos::iter().take(8)
 }
 
-pub fn e_slice_edges() -> impl Iterator<Item = EdgePos> + DoubleEndedIterator + ExactSizeIterator {
+pub fn e_slice_edges() -> impl DoubleEndedIterator<Item = EdgePos> + ExactSizeIterator {
     // EdgePos is organized to have the UD edges first
     EdgePos::iter().skip(8)
 }
@@ -12,9 +12,15 @@ use super::edge::EdgePerm;
 
 pub mod coord;
 pub mod notation;
+pub mod optimal;
 pub mod primitives;
+pub mod prune_table;
+pub mod solver;
 pub mod symmetry;
 
+pub use optimal::OptimalSolver;
+pub use solver::{SolveOptions, TwoPhaseSolver};
+
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Cube3Perm {
     corners: CornerPerm,
@@ -101,6 +107,8 @@ impl EnumIndex for CubeTurn {
 impl PuzzleMove for CubeTurn {
     type Puzzle = Cube3Perm;
 
+    const NAME: &'static str = "CubeTurn";
+
     fn permutation(&self) -> &Cube3Perm {
         use primitives::*;
         use CubeTurn::*;
@@ -140,15 +148,27 @@ impl PuzzleMove for CubeTurn {
         }
 
         use CubeTurn::*;
-        match (a, b) {
-            (U, U2) | (U, UP) | (U2, UP) => true,
-            (R, R2) | (R, RP) | (R2, RP) => true,
-            (F, F2) | (F, FP) | (F2, FP) => true,
-            (D, D2) | (D, DP) | (D2, DP) => true,
-            (L, L2) | (L, LP) | (L2, LP) => true,
-            (B, B2) | (B, BP) | (B2, BP) => true,
-            _ => false,
-        }
+        matches!(
+            (a, b),
+            (U, U2)
+                | (U, UP)
+                | (U2, UP)
+                | (R, R2)
+                | (R, RP)
+                | (R2, RP)
+                | (F, F2)
+                | (F, FP)
+                | (F2, FP)
+                | (D, D2)
+                | (D, DP)
+                | (D2, DP)
+                | (L, L2)
+                | (L, LP)
+                | (L2, LP)
+                | (B, B2)
+                | (B, BP)
+                | (B2, BP)
+        )
     }
 }
 
@@ -175,6 +195,8 @@ impl EnumIndex for G1CubeTurn {
 impl PuzzleMove for G1CubeTurn {
     type Puzzle = Cube3Perm;
 
+    const NAME: &'static str = "G1CubeTurn";
+
     fn permutation(&self) -> &Cube3Perm {
         use primitives::*;
         use G1CubeTurn::*;
@@ -206,10 +228,9 @@ impl PuzzleMove for G1CubeTurn {
         }
 
         use G1CubeTurn::*;
-        match (a, b) {
-            (U, U2) | (U, UP) | (U2, UP) => true,
-            (D, D2) | (D, DP) | (D2, DP) => true,
-            _ => false,
-        }
+        matches!(
+            (a, b),
+            (U, U2) | (U, UP) | (U2, UP) | (D, D2) | (D, DP) | (D2, DP)
+        )
     }
 }
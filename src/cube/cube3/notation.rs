@@ -1,10 +1,19 @@
+use std::fmt;
 use std::str::FromStr;
 
 use super::{Cube3Perm, CubeTurn, G1CubeTurn};
 
-use crate::notation::{NotationMove, NotationPrim};
+use crate::puzzle::PuzzlePerm;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumCount, EnumIter)]
+use crate::notation::{NotationError, NotationErrorReason, NotationMove, NotationPrim};
+
+/// Notation for the 3x3x3 cube.
+///
+/// Besides the outer face turns, this includes wide turns (2 layers), slice turns and rotations of
+/// the whole cube. Since the cube is modeled without centers, these are all face turns relative to
+/// the centers, along with a rotation of the frame that later moves are made in. For example, `M`
+/// is `R L'` followed by an `x'` rotation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumCount, EnumIter)]
 pub enum Cube3Notation {
     U,
     R,
@@ -12,27 +21,50 @@ pub enum Cube3Notation {
     D,
     L,
     B,
+    Uw,
+    Rw,
+    Fw,
+    Dw,
+    Lw,
+    Bw,
+    M,
+    E,
+    S,
+    X,
+    Y,
+    Z,
 }
 
-impl ToString for Cube3Notation {
-    fn to_string(&self) -> String {
+impl fmt::Display for Cube3Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use Cube3Notation::*;
-        match self {
+        f.write_str(match self {
             U => "U",
             R => "R",
             F => "F",
             D => "D",
             L => "L",
             B => "B",
-        }
-        .to_string()
+            Uw => "Uw",
+            Rw => "Rw",
+            Fw => "Fw",
+            Dw => "Dw",
+            Lw => "Lw",
+            Bw => "Bw",
+            M => "M",
+            E => "E",
+            S => "S",
+            X => "x",
+            Y => "y",
+            Z => "z",
+        })
     }
 }
 
 impl FromStr for Cube3Notation {
-    type Err = ();
+    type Err = NotationError;
 
-    fn from_str(s: &str) -> Result<Self, ()> {
+    fn from_str(s: &str) -> Result<Self, NotationError> {
         use Cube3Notation::*;
         match s {
             "U" => Ok(U),
@@ -41,11 +73,46 @@ impl FromStr for Cube3Notation {
             "D" => Ok(D),
             "L" => Ok(L),
             "B" => Ok(B),
-            _ => Err(()),
+            // Wide turns can also be written in lowercase
+            "Uw" | "u" => Ok(Uw),
+            "Rw" | "r" => Ok(Rw),
+            "Fw" | "f" => Ok(Fw),
+            "Dw" | "d" => Ok(Dw),
+            "Lw" | "l" => Ok(Lw),
+            "Bw" | "b" => Ok(Bw),
+            "M" => Ok(M),
+            "E" => Ok(E),
+            "S" => Ok(S),
+            "x" => Ok(X),
+            "y" => Ok(Y),
+            "z" => Ok(Z),
+            _ => Err(NotationError::new(
+                s,
+                0,
+                s.len(),
+                NotationErrorReason::UnknownPrim,
+            )),
         }
     }
 }
 
+mod slices {
+    use super::super::primitives::*;
+    use super::Cube3Perm;
+    use crate::puzzle::PuzzlePerm;
+
+    lazy_static! {
+        // Slice turns move the outer layers the opposite way relative to the centers
+        pub static ref M: Cube3Perm = r().sequence(l_prime());
+        pub static ref E: Cube3Perm = u().sequence(d_prime());
+        pub static ref S: Cube3Perm = f_prime().sequence(b());
+
+        pub static ref X_PRIME: Cube3Perm = x().invert();
+        pub static ref Y_PRIME: Cube3Perm = y().invert();
+        pub static ref Z_PRIME: Cube3Perm = z().invert();
+    }
+}
+
 impl NotationPrim for Cube3Notation {
     type Puzzle = Cube3Perm;
 
@@ -53,20 +120,38 @@ impl NotationPrim for Cube3Notation {
         use super::primitives::*;
         use Cube3Notation::*;
         match self {
-            U => u(),
-            R => r(),
-            F => f(),
-            D => d(),
-            L => l(),
-            B => b(),
+            U | Dw => u(),
+            R | Lw => r(),
+            F | Bw => f(),
+            D | Uw => d(),
+            L | Rw => l(),
+            B | Fw => b(),
+            M => &slices::M,
+            E => &slices::E,
+            S => &slices::S,
+            X | Y | Z => Cube3Perm::identity(),
         }
     }
-}
 
-impl Into<NotationMove<Cube3Notation>> for CubeTurn {
-    fn into(self) -> NotationMove<Cube3Notation> {
+    fn rotation(&self) -> Option<&Cube3Perm> {
+        use super::primitives::*;
         use Cube3Notation::*;
         match self {
+            U | R | F | D | L | B => None,
+            Uw | Y => Some(y()),
+            Rw | X => Some(x()),
+            Fw | Z | S => Some(z()),
+            Dw | E => Some(&slices::Y_PRIME),
+            Lw | M => Some(&slices::X_PRIME),
+            Bw => Some(&slices::Z_PRIME),
+        }
+    }
+}
+
+impl From<CubeTurn> for NotationMove<Cube3Notation> {
+    fn from(turn: CubeTurn) -> Self {
+        use Cube3Notation::*;
+        match turn {
             CubeTurn::U => NotationMove::basic(U),
             CubeTurn::U2 => NotationMove::ntimes(U, 2),
             CubeTurn::UP => NotationMove::inverse(U),
@@ -89,10 +174,10 @@ impl Into<NotationMove<Cube3Notation>> for CubeTurn {
     }
 }
 
-impl Into<NotationMove<Cube3Notation>> for G1CubeTurn {
-    fn into(self) -> NotationMove<Cube3Notation> {
+impl From<G1CubeTurn> for NotationMove<Cube3Notation> {
+    fn from(turn: G1CubeTurn) -> Self {
         use Cube3Notation::*;
-        match self {
+        match turn {
             G1CubeTurn::U => NotationMove::basic(U),
             G1CubeTurn::U2 => NotationMove::ntimes(U, 2),
             G1CubeTurn::UP => NotationMove::inverse(U),
@@ -106,3 +191,96 @@ impl Into<NotationMove<Cube3Notation>> for G1CubeTurn {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::notation::NotationStr;
+
+    fn notation(s: &str) -> NotationStr<Cube3Notation> {
+        s.parse().unwrap()
+    }
+
+    /// Checks that 2 notation strings have the same effect, including rotations
+    fn same_effect(a: &str, b: &str) {
+        let (a, b) = (notation(a), notation(b));
+        assert_eq!(a.permutation(), b.permutation(), "{} != {}", a, b);
+        assert_eq!(a.frame(), b.frame(), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn parses_and_prints_extended_moves() {
+        assert_eq!(
+            notation("Rw r' M2 E S' x y2 z' u d2").to_string(),
+            "Rw Rw' M2 E S' x y2 z' Uw Dw2"
+        );
+    }
+
+    #[test]
+    fn rotations_change_frame() {
+        same_effect("x U x'", "F");
+        same_effect("y F y'", "R");
+        same_effect("z U z'", "L");
+        same_effect("y y y y", "");
+    }
+
+    #[test]
+    fn wide_turns_are_face_and_slice_turns() {
+        same_effect("Rw", "R M'");
+        same_effect("Lw", "L M");
+        same_effect("Uw", "U E'");
+        same_effect("Dw", "D E");
+        same_effect("Fw", "F S");
+        same_effect("Bw", "B S'");
+    }
+
+    #[test]
+    fn slice_h_perm_matches_face_turns() {
+        same_effect("M2 U M2 U2 M2 U M2", "R2 U2 R U2 R2 U2 R2 U2 R U2 R2");
+    }
+
+    #[test]
+    fn extended_moves_have_order_4() {
+        use Cube3Notation::*;
+        for prim in [Uw, Rw, Fw, Dw, Lw, Bw, M, E, S, X, Y, Z] {
+            assert_eq!(prim.order(), 4, "Wrong order for {}", prim);
+        }
+    }
+
+    #[test]
+    fn moves_about_the_same_axis_commute() {
+        use Cube3Notation::*;
+        assert!(M.commutes_with(&R));
+        assert!(X.commutes_with(&Lw));
+        assert!(E.commutes_with(&Y));
+        assert!(!X.commutes_with(&U));
+        assert!(!M.commutes_with(&E));
+    }
+
+    #[test]
+    fn normalize_cancels_extended_moves() {
+        assert_eq!(notation("x M r' x' M' r").normalize(), notation(""));
+        assert_eq!(notation("y U y").normalize(), notation("U y2"));
+    }
+
+    #[test]
+    fn reframe_rotates_into_scramble_frame() {
+        let scramble = notation("x R U' y F2 Dw r");
+        let solution = notation("R U F' D2 L B'");
+        let reframed = solution.reframe(&scramble.frame());
+
+        let combined = notation(&format!("{} {}", scramble, reframed));
+        assert_eq!(
+            combined.permutation(),
+            scramble.permutation().sequence(&solution.permutation())
+        );
+        assert_eq!(combined.frame(), scramble.frame());
+    }
+
+    #[test]
+    fn reframe_without_rotation_is_unchanged() {
+        let solution = notation("R U F' D2 L B'");
+        assert_eq!(solution.reframe(Cube3Perm::identity()), solution);
+    }
+}
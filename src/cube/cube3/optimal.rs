@@ -0,0 +1,402 @@
+//! Optimal solver for the 3x3x3 cube.
+//!
+//! This is an IDA* search with the full `CubeTurn` move set, pruned by the largest of several lower
+//! bounds on the distance to the solved cube:
+//!
+//! - The number of moves to solve the corners. The table covers every corner permutation and
+//!   orientation, reduced by the 16 symmetries which fix the U-D axis, which makes it about 6
+//!   million entries.
+//! - The number of moves to reach G1 (the phase 1 subgroup of the two-phase solver) for each of the
+//!   3 axes. The cube is rotated so that each axis takes the place of the U-D axis, so the same
+//!   phase 1 table works for all of them. That table has about 140 million entries, so the tables
+//!   should be saved and loaded instead of built each time.
+
+use std::cmp;
+use std::io::{self, Read, Write};
+
+use crate::coord::Coord;
+use crate::cube::edge::EdgePerm;
+use crate::move_table::{BasicMoveTable, CompositeMoveTable, MoveTable};
+use crate::notation::{NotationMove, NotationStr};
+use crate::prune_table::{PruneTable, SymPruneTable, UNREACHABLE};
+use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::solver::{AbortReason, Aborted, SearchControl};
+use crate::symmetry::{conjugate, RepresentantTable};
+use crate::table_io::LoadError;
+use crate::util::{EnumIndex, IntoEnumIterator};
+
+use super::coord::{
+    CornerOrientCoord, CornerPosAndOrientCoord, CornerPosCoord, CornerPosSymCoord,
+    ESliceAndEOCoord, ESliceAndEOSymCoord, Phase1Coord,
+};
+use super::notation::Cube3Notation;
+use super::symmetry::{FullSymmetry, SymmetryE};
+use super::{Cube3Perm, CubeTurn};
+
+/// Number of axes the phase 1 coordinate is looked up on.
+const AXES: usize = 3;
+
+/// Solver for the 3x3x3 cube which finds the shortest solutions.
+///
+/// The search is single-threaded and always explores moves in the same order, so the same cube
+/// always gets the same solution. This is much slower than `TwoPhaseSolver`, especially for cubes
+/// which take close to 20 moves.
+pub struct OptimalSolver {
+    co_table: BasicMoveTable<CornerOrientCoord, CubeTurn>,
+    cp_table: BasicMoveTable<CornerPosCoord, CubeTurn>,
+    edge_table: BasicMoveTable<ESliceAndEOCoord, CubeTurn>,
+    /// Reduced by the 16 symmetries which fix the U-D axis rather than all 48. `SymPruneTable`
+    /// needs the orientation coordinate to be preserved by the symmetries, but orientation is
+    /// measured relative to the U-D axis, so the rotations which move that axis change it.
+    /// Reducing the whole corner coordinate by all 48 would need an index covering all 88 million
+    /// corner states instead.
+    corner_prune_table:
+        SymPruneTable<Cube3Perm, CornerPosSymCoord, CornerPosAndOrientCoord, CubeTurn>,
+    phase1_prune_table: SymPruneTable<Cube3Perm, ESliceAndEOSymCoord, Phase1Coord, CubeTurn>,
+}
+
+impl OptimalSolver {
+    /// Create a solver, building all of its tables.
+    pub fn create() -> Self {
+        info!("Building optimal solver tables");
+
+        let co_table = BasicMoveTable::create();
+        let cp_table = BasicMoveTable::create();
+        let edge_table = BasicMoveTable::create();
+        let corner_prune_table = SymPruneTable::create(
+            RepresentantTable::create(),
+            &cp_table,
+            &co_table,
+            CornerPosAndOrientCoord::default(),
+        );
+        let phase1_prune_table = SymPruneTable::create(
+            RepresentantTable::create(),
+            &edge_table,
+            &co_table,
+            Phase1Coord::default(),
+        );
+
+        Self {
+            co_table,
+            cp_table,
+            edge_table,
+            corner_prune_table,
+            phase1_prune_table,
+        }
+    }
+
+    /// Write all of the solver's tables so that they don't have to be built again.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.co_table.save(&mut writer)?;
+        self.cp_table.save(&mut writer)?;
+        self.edge_table.save(&mut writer)?;
+        self.corner_prune_table.save(&mut writer)?;
+        self.phase1_prune_table.save(&mut writer)
+    }
+
+    /// Read tables written by `save`.
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, LoadError> {
+        Ok(Self {
+            co_table: BasicMoveTable::load(&mut reader)?,
+            cp_table: BasicMoveTable::load(&mut reader)?,
+            edge_table: BasicMoveTable::load(&mut reader)?,
+            corner_prune_table: SymPruneTable::load(&mut reader)?,
+            phase1_prune_table: SymPruneTable::load(&mut reader)?,
+        })
+    }
+
+    /// Find a shortest solution for a cube state.
+    pub fn solve(
+        &self,
+        perm: &Cube3Perm,
+        control: &SearchControl,
+    ) -> Result<NotationStr<Cube3Notation>, Aborted> {
+        let moves = Search::new(self, perm, control).run()?;
+
+        Ok(NotationStr::from(
+            moves
+                .iter()
+                .map(|m| NotationMove::from(*m))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    fn node_from_perm(&self, perm: &Cube3Perm) -> Node {
+        Node {
+            edges: perm.edges().clone(),
+            corners: CornerPosAndOrientCoord::from_perm(perm),
+            phase1: std::array::from_fn(|axis| {
+                Phase1Coord::from_perm(&conjugate(&axis_symmetry(axis), perm))
+            }),
+        }
+    }
+
+    fn apply_move(&self, node: &Node, axis_moves: &AxisMoves, mov: CubeTurn) -> Node {
+        let corner_table = CompositeMoveTable::new(&self.cp_table, &self.co_table);
+        let phase1_table = CompositeMoveTable::new(&self.edge_table, &self.co_table);
+
+        Node {
+            edges: node.edges.sequence(mov.permutation().edges()),
+            corners: corner_table.get_move(node.corners, mov),
+            phase1: std::array::from_fn(|axis| {
+                phase1_table.get_move(node.phase1[axis], axis_moves[axis][mov.index()])
+            }),
+        }
+    }
+
+    /// Lower bound on the number of moves to solve a node.
+    fn min_moves(&self, node: &Node) -> u32 {
+        node.phase1
+            .iter()
+            .map(|&coord| self.phase1_prune_table.get_min_moves(coord))
+            .fold(
+                self.corner_prune_table.get_min_moves(node.corners),
+                cmp::max,
+            )
+    }
+}
+
+/// Symmetry which takes the U-D axis to another axis.
+fn axis_symmetry(axis: usize) -> FullSymmetry {
+    FullSymmetry::new(axis as u8, SymmetryE::default())
+}
+
+/// Each move conjugated by the symmetry for each axis, indexed by the move.
+type AxisMoves = [Vec<CubeTurn>; AXES];
+
+fn axis_moves() -> AxisMoves {
+    std::array::from_fn(|axis| {
+        let sym = axis_symmetry(axis);
+        CubeTurn::iter()
+            .map(|m| {
+                let perm = conjugate(&sym, m.permutation());
+                // Rotations take face turns to face turns
+                CubeTurn::iter()
+                    .find(|conj| conj.permutation() == &perm)
+                    .unwrap()
+            })
+            .collect()
+    })
+}
+
+/// Whether a move can be skipped after another one.
+///
+/// Turns of the same face combine into a single turn, and turns of opposite faces commute, so only
+/// one order of them has to be searched. `CubeTurn` has 3 turns for each face, ordered U, R, F, D,
+/// L, B, so opposite faces are 3 apart.
+fn is_redundant(prev: CubeTurn, next: CubeTurn) -> bool {
+    let prev_face = prev.index() / 3;
+    let next_face = next.index() / 3;
+    prev_face == next_face || prev_face == next_face + 3
+}
+
+/// Coordinates of a cube state used in the search.
+#[derive(Clone, Debug)]
+struct Node {
+    /// The edges aren't covered by any one coordinate, so they are kept to check for a solution
+    edges: EdgePerm,
+    corners: CornerPosAndOrientCoord,
+    /// Phase 1 coordinate of the cube conjugated by the symmetry for each axis
+    phase1: [Phase1Coord; AXES],
+}
+
+/// State of an iterative deepening search.
+struct Search<'a> {
+    solver: &'a OptimalSolver,
+    perm: &'a Cube3Perm,
+    control: &'a SearchControl,
+    axis_moves: AxisMoves,
+    path: Vec<CubeTurn>,
+    max_depth: u32,
+    /// Smallest depth which was pruned in the current iteration
+    next_max_depth: u32,
+    nodes: u64,
+}
+
+impl<'a> Search<'a> {
+    fn new(solver: &'a OptimalSolver, perm: &'a Cube3Perm, control: &'a SearchControl) -> Self {
+        Self {
+            solver,
+            perm,
+            control,
+            axis_moves: axis_moves(),
+            path: Vec::new(),
+            max_depth: 0,
+            next_max_depth: u32::MAX,
+            nodes: 0,
+        }
+    }
+
+    fn run(mut self) -> Result<Vec<CubeTurn>, Aborted> {
+        if self.perm.is_identity() {
+            return Ok(Vec::new());
+        }
+
+        let root = self.solver.node_from_perm(self.perm);
+        let bound = self.solver.min_moves(&root);
+        if bound >= UNREACHABLE {
+            return Err(self.abort(AbortReason::Exhausted));
+        }
+
+        self.max_depth = cmp::max(bound, 1);
+        loop {
+            if let Err(reason) = self.control.check_depth(self.max_depth) {
+                return Err(self.abort(reason));
+            }
+
+            self.next_max_depth = u32::MAX;
+            match self.search(&root) {
+                Ok(true) => return Ok(self.path),
+                Ok(false) => {}
+                Err(reason) => return Err(self.abort(reason)),
+            }
+
+            // Every branch was pruned as unreachable
+            if self.next_max_depth == u32::MAX {
+                return Err(self.abort(AbortReason::Exhausted));
+            }
+            self.max_depth = self.next_max_depth;
+        }
+    }
+
+    /// Search the moves after the current path, returning whether a solution was found.
+    ///
+    /// The solution is left in `path`.
+    fn search(&mut self, node: &Node) -> Result<bool, AbortReason> {
+        let depth = self.path.len() as u32;
+        if depth == self.max_depth {
+            // All of the bounds are 0 here, so the corners are solved, but that doesn't mean the
+            // edges are
+            if node.edges.is_identity() {
+                return Ok(true);
+            }
+            self.next_max_depth = cmp::min(self.next_max_depth, self.max_depth + 1);
+            return Ok(false);
+        }
+
+        for mov in CubeTurn::iter() {
+            if self
+                .path
+                .last()
+                .is_some_and(|&prev| is_redundant(prev, mov))
+            {
+                continue;
+            }
+
+            self.visit_node()?;
+
+            let next = self.solver.apply_move(node, &self.axis_moves, mov);
+            let bound = self.solver.min_moves(&next);
+            let heuristic_depth = depth + 1 + bound;
+            if heuristic_depth > self.max_depth {
+                if bound < UNREACHABLE {
+                    self.next_max_depth = cmp::min(self.next_max_depth, heuristic_depth);
+                }
+                continue;
+            }
+
+            self.path.push(mov);
+            if self.search(&next)? {
+                return Ok(true);
+            }
+            self.path.pop();
+        }
+
+        Ok(false)
+    }
+
+    /// Count a visited node and check the limits.
+    fn visit_node(&mut self) -> Result<(), AbortReason> {
+        self.nodes += 1;
+        self.control.check_nodes(self.nodes)?;
+        if self.nodes.is_multiple_of(SearchControl::CHECK_INTERVAL) {
+            self.control.check_interrupt()?;
+        }
+        Ok(())
+    }
+
+    fn abort(&self, reason: AbortReason) -> Aborted {
+        Aborted {
+            reason,
+            depth: self.max_depth,
+            nodes: self.nodes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    lazy_static! {
+        static ref SOLVER: OptimalSolver = OptimalSolver::create();
+    }
+
+    fn parse(moves: &str) -> Cube3Perm {
+        moves
+            .parse::<NotationStr<Cube3Notation>>()
+            .unwrap()
+            .permutation()
+    }
+
+    #[test]
+    fn finds_shortest_solutions() {
+        // Scrambles with known optimal lengths
+        let scrambles = [
+            ("R", 1),
+            ("R U", 2),
+            ("R U R' U'", 4),
+            ("U2 D2 F2 B2 L2 R2", 6),
+            ("R U F' L2 D B' R2", 7),
+            // Only the edges are unsolved, so every bound is 0 at many unsolved leaves
+            ("R2 L2 U2 R2 L2 D2", 6),
+            ("R2 U R U R' U' R' U' R' U R'", 9),
+        ];
+
+        for (scramble, length) in scrambles {
+            let perm = parse(scramble);
+            let solution = SOLVER.solve(&perm, &SearchControl::default()).unwrap();
+            assert_eq!(solution.len(), length, "{} for {}", solution, scramble);
+            assert_eq!(
+                &perm.sequence(&solution.permutation()),
+                Cube3Perm::identity(),
+                "{} does not solve {}",
+                solution,
+                scramble
+            );
+
+            // There is nothing shorter
+            let control = SearchControl {
+                max_depth: Some(length as u32 - 1),
+                ..Default::default()
+            };
+            let aborted = SOLVER.solve(&perm, &control).unwrap_err();
+            assert_eq!(aborted.reason, AbortReason::MaxDepth);
+        }
+    }
+
+    #[test]
+    fn solved_cube_has_empty_solution() {
+        let solution = SOLVER
+            .solve(Cube3Perm::identity(), &SearchControl::default())
+            .unwrap();
+        assert_eq!(solution.len(), 0);
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let mut saved = Vec::new();
+        SOLVER.save(&mut saved).unwrap();
+
+        let loaded = OptimalSolver::load(saved.as_slice()).unwrap();
+        let mut resaved = Vec::new();
+        loaded.save(&mut resaved).unwrap();
+        assert!(saved == resaved, "Tables changed after loading");
+
+        let perm = parse("R U F' L2 D B' R2");
+        assert_eq!(
+            loaded.solve(&perm, &SearchControl::default()).unwrap(),
+            SOLVER.solve(&perm, &SearchControl::default()).unwrap()
+        );
+    }
+}
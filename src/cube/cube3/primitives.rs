@@ -1,6 +1,8 @@
 use crate::cube::cube2::primitives as corner_prim;
-use crate::puzzle::PuzzlePerm;
+use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::symmetry::conjugate;
 
+use super::symmetry::{FullSymmetry, SymmetryE};
 use super::Cube3Perm;
 
 mod edge_prim {
@@ -271,6 +273,37 @@ pub fn b2() -> &'static Cube3Perm {
     &B2
 }
 
+/// Rotation of the whole cube in the direction of R.
+pub fn x() -> &'static Cube3Perm {
+    lazy_static! {
+        // The URF rotation takes the U-D axis to the R-L axis when inverted
+        static ref X: Cube3Perm = conjugate(
+            &FullSymmetry::new(1, SymmetryE::default()).invert(),
+            y(),
+        );
+    }
+
+    &X
+}
+
+/// Rotation of the whole cube in the direction of U.
+pub fn y() -> &'static Cube3Perm {
+    lazy_static! {
+        static ref Y: Cube3Perm = SymmetryE::new(1, 0, 0).permutation().clone();
+    }
+
+    &Y
+}
+
+/// Rotation of the whole cube in the direction of F.
+pub fn z() -> &'static Cube3Perm {
+    lazy_static! {
+        static ref Z: Cube3Perm = conjugate(&FullSymmetry::new(1, SymmetryE::default()), y());
+    }
+
+    &Z
+}
+
 // TODO These tests are identical to the 2x2x2 ones
 
 #[cfg(test)]
@@ -368,6 +401,40 @@ mod test {
         }
     }
 
+    mod rotations {
+        use super::*;
+
+        /// Rotating, turning and rotating back turns the face which was rotated into place
+        fn turns_face(rot: &Cube3Perm, turn: &Cube3Perm, expected: &Cube3Perm) {
+            assert_eq!(&rot.sequence(turn).sequence(&rot.invert()), expected);
+        }
+
+        #[test]
+        fn rotations_have_order_4() {
+            has_order(x(), 4);
+            has_order(y(), 4);
+            has_order(z(), 4);
+        }
+
+        #[test]
+        fn x_brings_f_to_u() {
+            turns_face(x(), u(), f());
+            turns_face(x(), r(), r());
+        }
+
+        #[test]
+        fn y_brings_r_to_f() {
+            turns_face(y(), f(), r());
+            turns_face(y(), u(), u());
+        }
+
+        #[test]
+        fn z_brings_l_to_u() {
+            turns_face(z(), u(), l());
+            turns_face(z(), f(), f());
+        }
+    }
+
     mod opposite_turns_commute {
         use super::*;
 
@@ -0,0 +1,125 @@
+//! Pruning tables specific to the 3x3x3 cube.
+
+use crate::prune_table::PruneTable;
+
+use super::coord::{CornerPosAndESliceCoord, Phase2Coord, UdEdgePosCoord};
+use super::{Cube3Perm, G1CubeTurn};
+
+/// Pruning table for phase 2.
+///
+/// The corners and E slice edges are looked up together, so the table for them can be reduced by
+/// symmetry (see `SymPruneTable`). The greatest lower bound between that and the U and D edges is
+/// returned.
+pub struct Phase2PruneTable<'a, CT, ET> {
+    corner_table: &'a CT,
+    ud_edge_table: &'a ET,
+}
+
+impl<'a, CT, ET> Phase2PruneTable<'a, CT, ET>
+where
+    CT: PruneTable<Puzzle = Cube3Perm, Coord = CornerPosAndESliceCoord, Move = G1CubeTurn>,
+    ET: PruneTable<Puzzle = Cube3Perm, Coord = UdEdgePosCoord, Move = G1CubeTurn>,
+{
+    pub fn new(corner_table: &'a CT, ud_edge_table: &'a ET) -> Self {
+        Self {
+            corner_table,
+            ud_edge_table,
+        }
+    }
+}
+
+impl<'a, CT, ET> PruneTable for Phase2PruneTable<'a, CT, ET>
+where
+    CT: PruneTable<Puzzle = Cube3Perm, Coord = CornerPosAndESliceCoord, Move = G1CubeTurn>,
+    ET: PruneTable<Puzzle = Cube3Perm, Coord = UdEdgePosCoord, Move = G1CubeTurn>,
+{
+    type Puzzle = Cube3Perm;
+    type Coord = Phase2Coord;
+    type Move = G1CubeTurn;
+
+    fn get_min_moves(&self, coord: Phase2Coord) -> u32 {
+        let ud_cubies = coord.ud_cubies();
+        let corners = CornerPosAndESliceCoord::new(ud_cubies.corners(), coord.e_edges());
+        std::cmp::max(
+            self.corner_table.get_min_moves(corners),
+            self.ud_edge_table.get_min_moves(ud_cubies.edges()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::coord::Coord;
+    use crate::cube::cube3::coord::{CornerPosCoord, CornerPosSymCoord, ESliceEdgePosCoord};
+    use crate::move_table::BasicMoveTable;
+    use crate::prune_table::{FullPruneTable, SymPruneTable};
+    use crate::puzzle::{PuzzleMove, PuzzlePerm};
+    use crate::symmetry::RepresentantTable;
+    use crate::util::IntoEnumIterator;
+
+    lazy_static! {
+        static ref CORNER_TABLE: SymPruneTable<Cube3Perm, CornerPosSymCoord, CornerPosAndESliceCoord, G1CubeTurn> = {
+            let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+            let eslice_table: BasicMoveTable<ESliceEdgePosCoord, G1CubeTurn> =
+                BasicMoveTable::create();
+            SymPruneTable::create(
+                RepresentantTable::create(),
+                &cp_table,
+                &eslice_table,
+                CornerPosAndESliceCoord::default(),
+            )
+        };
+        static ref UD_EDGE_TABLE: FullPruneTable<UdEdgePosCoord, G1CubeTurn> =
+            FullPruneTable::create(&BasicMoveTable::create(), UdEdgePosCoord::default());
+    }
+
+    fn phase2_prune_table() -> Phase2PruneTable<
+        'static,
+        SymPruneTable<Cube3Perm, CornerPosSymCoord, CornerPosAndESliceCoord, G1CubeTurn>,
+        FullPruneTable<UdEdgePosCoord, G1CubeTurn>,
+    > {
+        Phase2PruneTable::new(&*CORNER_TABLE, &*UD_EDGE_TABLE)
+    }
+
+    #[test]
+    fn solved_state_needs_no_moves() {
+        assert_eq!(
+            phase2_prune_table().get_min_moves(Phase2Coord::default()),
+            0
+        );
+    }
+
+    #[test]
+    fn single_move_needs_one_move() {
+        let table = phase2_prune_table();
+        for m in G1CubeTurn::iter() {
+            let coord = Phase2Coord::from_perm(m.permutation());
+            assert_eq!(table.get_min_moves(coord), 1, "Wrong bound for {:?}", m);
+        }
+    }
+
+    #[test]
+    fn bound_is_admissible() {
+        use G1CubeTurn::*;
+
+        let table = phase2_prune_table();
+        let scramble = [U, R2, DP, F2, L2, U2, B2, D];
+
+        // The scramble undone backwards is a solution, so each state along it is at most the
+        // number of moves applied so far
+        let mut perm = Cube3Perm::identity().clone();
+        for (i, m) in scramble.iter().enumerate() {
+            perm = perm.sequence(m.permutation());
+            let bound = table.get_min_moves(Phase2Coord::from_perm(&perm));
+            assert!(
+                bound as usize <= i + 1,
+                "Bound {} is more than {} after {:?}",
+                bound,
+                i + 1,
+                &scramble[..=i]
+            );
+        }
+    }
+}
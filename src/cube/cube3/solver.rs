@@ -0,0 +1,350 @@
+//! Two-phase solver for the 3x3x3 cube.
+//!
+//! Phase 1 brings the cube into the subgroup G1 = <U, D, R2, F2, L2, B2>, where all of the pieces
+//! are oriented and the E slice edges are in the E slice. Phase 2 then solves the cube using only
+//! moves from G1.
+
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+use crate::coord::Coord;
+use crate::move_table::{BasicMoveTable, CompositeMoveTable};
+use crate::notation::{NotationMove, NotationStr};
+use crate::prune_table::{FullPruneTable, SymPruneTable};
+use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::solver::{AbortReason, SearchControl, SolutionIter};
+use crate::symmetry::RepresentantTable;
+use crate::table_io::LoadError;
+
+use super::coord::{
+    CornerOrientCoord, CornerPosAndESliceCoord, CornerPosCoord, CornerPosSymCoord,
+    ESliceAndEOCoord, ESliceAndEOSymCoord, ESliceEdgePosCoord, Phase1Coord, Phase2Coord,
+    UdEdgePosCoord,
+};
+use super::notation::Cube3Notation;
+use super::prune_table::Phase2PruneTable;
+use super::{Cube3Perm, CubeTurn, G1CubeTurn};
+
+/// Options for `TwoPhaseSolver::solve`.
+#[derive(Clone, Debug)]
+pub struct SolveOptions {
+    /// Maximum number of moves in a solution.
+    pub max_length: Option<usize>,
+
+    /// Maximum number of solutions to return.
+    pub num_solutions: usize,
+
+    /// Time budget for the search.
+    pub time_limit: Option<Duration>,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self {
+            max_length: None,
+            num_solutions: 1,
+            time_limit: None,
+        }
+    }
+}
+
+/// Solver for the 3x3x3 cube using Kociemba's two-phase algorithm.
+///
+/// This holds all of the move and pruning tables used in the search, which take a while to build,
+/// so a single solver should be used for multiple solves.
+pub struct TwoPhaseSolver {
+    co_table: BasicMoveTable<CornerOrientCoord, CubeTurn>,
+    phase1_edge_table: BasicMoveTable<ESliceAndEOCoord, CubeTurn>,
+    phase1_prune_table: SymPruneTable<Cube3Perm, ESliceAndEOSymCoord, Phase1Coord, CubeTurn>,
+
+    cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn>,
+    ud_ep_table: BasicMoveTable<UdEdgePosCoord, G1CubeTurn>,
+    phase2_eslice_table: BasicMoveTable<ESliceEdgePosCoord, G1CubeTurn>,
+    cp_eslice_prune_table:
+        SymPruneTable<Cube3Perm, CornerPosSymCoord, CornerPosAndESliceCoord, G1CubeTurn>,
+    ud_ep_prune_table: FullPruneTable<UdEdgePosCoord, G1CubeTurn>,
+}
+
+impl TwoPhaseSolver {
+    /// Create a solver, building all of its tables.
+    pub fn create() -> Self {
+        info!("Building phase 1 tables");
+
+        let co_table = BasicMoveTable::create();
+        let phase1_edge_table = BasicMoveTable::create();
+        let phase1_prune_table = SymPruneTable::create(
+            RepresentantTable::create(),
+            &phase1_edge_table,
+            &co_table,
+            Phase1Coord::default(),
+        );
+
+        info!("Building phase 2 tables");
+
+        let cp_table = BasicMoveTable::create();
+        let ud_ep_table = BasicMoveTable::create();
+        let phase2_eslice_table = BasicMoveTable::create();
+        let cp_eslice_prune_table = SymPruneTable::create(
+            RepresentantTable::create(),
+            &cp_table,
+            &phase2_eslice_table,
+            CornerPosAndESliceCoord::default(),
+        );
+        let ud_ep_prune_table = FullPruneTable::create(&ud_ep_table, UdEdgePosCoord::default());
+
+        Self {
+            co_table,
+            phase1_edge_table,
+            phase1_prune_table,
+            cp_table,
+            ud_ep_table,
+            phase2_eslice_table,
+            cp_eslice_prune_table,
+            ud_ep_prune_table,
+        }
+    }
+
+    /// Write all of the solver's tables so that they don't have to be built again.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.co_table.save(&mut writer)?;
+        self.phase1_edge_table.save(&mut writer)?;
+        self.phase1_prune_table.save(&mut writer)?;
+        self.cp_table.save(&mut writer)?;
+        self.ud_ep_table.save(&mut writer)?;
+        self.phase2_eslice_table.save(&mut writer)?;
+        self.cp_eslice_prune_table.save(&mut writer)?;
+        self.ud_ep_prune_table.save(&mut writer)
+    }
+
+    /// Read tables written by `save`.
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, LoadError> {
+        Ok(Self {
+            co_table: BasicMoveTable::load(&mut reader)?,
+            phase1_edge_table: BasicMoveTable::load(&mut reader)?,
+            phase1_prune_table: SymPruneTable::load(&mut reader)?,
+            cp_table: BasicMoveTable::load(&mut reader)?,
+            ud_ep_table: BasicMoveTable::load(&mut reader)?,
+            phase2_eslice_table: BasicMoveTable::load(&mut reader)?,
+            cp_eslice_prune_table: SymPruneTable::load(&mut reader)?,
+            ud_ep_prune_table: FullPruneTable::load(&mut reader)?,
+        })
+    }
+
+    /// Find solutions for a cube state.
+    ///
+    /// Solutions are returned in the order they are found. Phase 1 solutions are tried shortest
+    /// first, and each is paired with the shortest phase 2 solution following it, so the total
+    /// lengths are not necessarily in order.
+    pub fn solve(
+        &self,
+        perm: &Cube3Perm,
+        options: &SolveOptions,
+    ) -> Vec<NotationStr<Cube3Notation>> {
+        let start = Instant::now();
+        let mut solutions = Vec::new();
+        // Different phase 1 solutions can give the same solution once normalized
+        let mut seen = HashSet::new();
+
+        if options.num_solutions == 0 {
+            return solutions;
+        }
+
+        let phase1_move_table = CompositeMoveTable::new(&self.phase1_edge_table, &self.co_table);
+
+        let phase2_minus_e_table = CompositeMoveTable::new(&self.cp_table, &self.ud_ep_table);
+        let phase2_move_table =
+            CompositeMoveTable::new(&phase2_minus_e_table, &self.phase2_eslice_table);
+        let phase2_prune_table =
+            Phase2PruneTable::new(&self.cp_eslice_prune_table, &self.ud_ep_prune_table);
+
+        let phase1_control = SearchControl {
+            max_depth: options.max_length.map(|max| max as u32),
+            deadline: options.time_limit.map(|limit| start + limit),
+            ..Default::default()
+        };
+        let phase1_solutions = SolutionIter::new(
+            &phase1_move_table,
+            &self.phase1_prune_table,
+            Phase1Coord::default(),
+            perm,
+        )
+        .with_control(phase1_control.clone());
+
+        for phase1_moves in phase1_solutions {
+            // Phase 1 solutions only get longer, so none of the rest will fit
+            if options
+                .max_length
+                .is_some_and(|max| phase1_moves.len() > max)
+            {
+                break;
+            }
+
+            // If the last move is also a phase 2 move, then the moves before it are a shorter
+            // phase 1 solution which has already been tried.
+            if phase1_moves.last().is_some_and(is_phase2_move) {
+                continue;
+            }
+
+            let phase1_perm = phase1_moves
+                .iter()
+                .fold(perm.clone(), |perm, m| perm.sequence(m.permutation()));
+            debug_assert_eq!(Phase1Coord::from_perm(&phase1_perm), Phase1Coord::default());
+
+            // Phase 2 only gets the moves left over from phase 1, and stops at the same deadline
+            let phase2_control = SearchControl {
+                max_depth: options
+                    .max_length
+                    .map(|max| (max - phase1_moves.len()) as u32),
+                ..phase1_control.clone()
+            };
+            let mut phase2_solutions = SolutionIter::new(
+                &phase2_move_table,
+                &phase2_prune_table,
+                Phase2Coord::default(),
+                &phase1_perm,
+            )
+            .with_control(phase2_control);
+
+            // The first phase 2 solution is the shortest
+            match phase2_solutions.next() {
+                Some(phase2_moves) => {
+                    let moves = phase1_moves
+                        .iter()
+                        .map(|m| NotationMove::from(*m))
+                        .chain(phase2_moves.iter().map(|m| NotationMove::from(*m)))
+                        .collect::<Vec<_>>();
+                    // The end of phase 1 can combine with the start of phase 2
+                    let solution = NotationStr::from(moves).normalize();
+                    if seen.insert(solution.clone()) {
+                        solutions.push(solution);
+
+                        if solutions.len() >= options.num_solutions {
+                            break;
+                        }
+                    }
+                }
+                None => match phase2_solutions.aborted().map(|aborted| aborted.reason) {
+                    // Another phase 1 solution may leave a shorter phase 2
+                    Some(AbortReason::MaxDepth) => {}
+                    _ => break,
+                },
+            }
+
+            if options
+                .time_limit
+                .is_some_and(|limit| start.elapsed() >= limit)
+            {
+                break;
+            }
+        }
+
+        solutions
+    }
+}
+
+/// Whether a phase 1 move is also a phase 2 move.
+fn is_phase2_move(m: &CubeTurn) -> bool {
+    use CubeTurn::*;
+    matches!(m, U | U2 | UP | D | D2 | DP | R2 | F2 | L2 | B2)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    lazy_static! {
+        static ref SOLVER: TwoPhaseSolver = TwoPhaseSolver::create();
+    }
+
+    fn scramble() -> Cube3Perm {
+        "R U F' L2 D B' R2 U'"
+            .parse::<NotationStr<Cube3Notation>>()
+            .unwrap()
+            .permutation()
+    }
+
+    fn assert_solves(perm: &Cube3Perm, solution: &NotationStr<Cube3Notation>) {
+        assert_eq!(
+            &perm.sequence(&solution.permutation()),
+            Cube3Perm::identity(),
+            "{} is not a solution",
+            solution
+        );
+    }
+
+    #[test]
+    fn solutions_solve_the_cube() {
+        let perm = scramble();
+        let options = SolveOptions {
+            num_solutions: 5,
+            ..Default::default()
+        };
+
+        let solutions = SOLVER.solve(&perm, &options);
+        assert_eq!(solutions.len(), 5);
+        for solution in &solutions {
+            assert_solves(&perm, solution);
+        }
+
+        let distinct = solutions.iter().collect::<HashSet<_>>();
+        assert_eq!(distinct.len(), solutions.len(), "Repeated solutions");
+    }
+
+    #[test]
+    fn max_length_is_respected() {
+        let perm = scramble();
+        let options = SolveOptions {
+            max_length: Some(10),
+            num_solutions: 3,
+            ..Default::default()
+        };
+
+        let solutions = SOLVER.solve(&perm, &options);
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            assert!(solution.len() <= 10, "{} is too long", solution);
+            assert_solves(&perm, solution);
+        }
+    }
+
+    #[test]
+    fn max_length_bounds_phase2() {
+        let perm = scramble();
+        // Every solution has to come from a phase 1 solution short enough to leave room for its
+        // phase 2, so this finishes once all of the phase 1 solutions have been tried
+        let options = SolveOptions {
+            max_length: Some(8),
+            num_solutions: usize::MAX,
+            ..Default::default()
+        };
+
+        let solutions = SOLVER.solve(&perm, &options);
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            assert!(solution.len() <= 8, "{} is too long", solution);
+            assert_solves(&perm, solution);
+        }
+    }
+
+    #[test]
+    fn time_limit_stops_search() {
+        let perm = scramble();
+        // Without a time limit, this would search forever since there aren't that many solutions
+        // which are found quickly
+        let options = SolveOptions {
+            num_solutions: usize::MAX,
+            time_limit: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+
+        // Build the tables first so that they aren't timed
+        let solver = &*SOLVER;
+        let start = Instant::now();
+        let solutions = solver.solve(&perm, &options);
+        assert!(start.elapsed() < Duration::from_secs(10));
+        for solution in &solutions {
+            assert_solves(&perm, solution);
+        }
+    }
+}
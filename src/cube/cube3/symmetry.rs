@@ -1,21 +1,17 @@
 //! Symmetries of a 3x3x3 cube.
 
 use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::symmetry::Symmetry;
 use crate::util::{EnumCount, EnumIndex, IntoEnumIterator};
 
 use super::Cube3Perm;
 
 make_newtype_enum_index! {
     /// The 16 cube symmetries which preserve the E slice.
+    #[derive(Default)]
     pub struct SymmetryE(u8) : 16;
 }
 
-impl Default for SymmetryE {
-    fn default() -> Self {
-        Self(0)
-    }
-}
-
 impl SymmetryE {
     pub fn new(y_rot: u8, x2_rot: u8, m_refl: u8) -> Self {
         debug_assert!(y_rot < 4);
@@ -41,6 +37,8 @@ impl SymmetryE {
 impl PuzzleMove for SymmetryE {
     type Puzzle = Cube3Perm;
 
+    const NAME: &'static str = "SymmetryE";
+
     fn permutation(&self) -> &Cube3Perm {
         lazy_static! {
             static ref TABLE: [Cube3Perm; SymmetryE::COUNT] = {
@@ -74,12 +72,83 @@ fn sym_perm(sym: &SymmetryE) -> &Cube3Perm {
 impl_puzzle_perm_with_tables!(
     SymmetryE,
     || {
-        const IDENTITY: SymmetryE = Self(0);
+        const IDENTITY: SymmetryE = SymmetryE(0);
         &IDENTITY
     },
     sym_perm
 );
 
+impl Symmetry for SymmetryE {}
+
+make_newtype_enum_index! {
+    /// All 48 cube symmetries: the 24 rotations, and each of them combined with a reflection.
+    ///
+    /// Each one is a rotation about the URF-DBL axis (taking the U-D axis to the other axes)
+    /// followed by one of the symmetries which preserve the E slice.
+    #[derive(Default)]
+    pub struct FullSymmetry(u8) : 48;
+}
+
+impl FullSymmetry {
+    pub fn new(urf_rot: u8, sym_e: SymmetryE) -> Self {
+        debug_assert!(urf_rot < 3);
+
+        Self(urf_rot * SymmetryE::COUNT as u8 + sym_e.0)
+    }
+
+    pub const fn urf_rot(&self) -> u8 {
+        self.0 / SymmetryE::COUNT as u8
+    }
+
+    pub const fn sym_e(&self) -> SymmetryE {
+        SymmetryE(self.0 % SymmetryE::COUNT as u8)
+    }
+}
+
+impl From<SymmetryE> for FullSymmetry {
+    fn from(sym: SymmetryE) -> Self {
+        Self::new(0, sym)
+    }
+}
+
+impl PuzzleMove for FullSymmetry {
+    type Puzzle = Cube3Perm;
+
+    const NAME: &'static str = "FullSymmetry";
+
+    fn permutation(&self) -> &Cube3Perm {
+        lazy_static! {
+            static ref TABLE: [Cube3Perm; FullSymmetry::COUNT] = std::array::from_fn(|i| {
+                let sym = FullSymmetry(i as u8);
+                primitives::URF_ROT
+                    .ntimes(sym.urf_rot())
+                    .sequence(sym.sym_e().permutation())
+            });
+        }
+
+        &TABLE[self.0 as usize]
+    }
+
+    fn combines_with(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+fn full_sym_perm(sym: &FullSymmetry) -> &Cube3Perm {
+    sym.permutation()
+}
+
+impl_puzzle_perm_with_tables!(
+    FullSymmetry,
+    || {
+        const IDENTITY: FullSymmetry = FullSymmetry(0);
+        &IDENTITY
+    },
+    full_sym_perm
+);
+
+impl Symmetry for FullSymmetry {}
+
 mod primitives {
     use super::*;
     use crate::cube::corner::{Corner, CornerOrient, CornerPerm, CornerPos::*};
@@ -99,17 +168,17 @@ mod primitives {
         ]),
         EdgePerm::new([
             Edge::new(UR, EdgeOrient::Oriented),
-            Edge::new(UB, EdgeOrient::Oriented),
-            Edge::new(UL, EdgeOrient::Oriented),
             Edge::new(UF, EdgeOrient::Oriented),
+            Edge::new(UL, EdgeOrient::Oriented),
+            Edge::new(UB, EdgeOrient::Oriented),
             Edge::new(DR, EdgeOrient::Oriented),
             Edge::new(DB, EdgeOrient::Oriented),
             Edge::new(DL, EdgeOrient::Oriented),
             Edge::new(DF, EdgeOrient::Oriented),
             Edge::new(BR, EdgeOrient::NotOriented),
-            Edge::new(BL, EdgeOrient::NotOriented),
-            Edge::new(FL, EdgeOrient::NotOriented),
             Edge::new(FR, EdgeOrient::NotOriented),
+            Edge::new(FL, EdgeOrient::NotOriented),
+            Edge::new(BL, EdgeOrient::NotOriented),
         ]),
     );
 
@@ -141,9 +210,37 @@ mod primitives {
         ]),
     );
 
+    /// 120 degree rotation about the URF-DBL axis, taking U to R, R to F and F to U
+    pub const URF_ROT: Cube3Perm = Cube3Perm::new(
+        CornerPerm::new([
+            Corner::new(DLF, CornerOrient::Clockwise),
+            Corner::new(UFL, CornerOrient::AntiClockwise),
+            Corner::new(URF, CornerOrient::Clockwise),
+            Corner::new(DFR, CornerOrient::AntiClockwise),
+            Corner::new(DRB, CornerOrient::Clockwise),
+            Corner::new(UBR, CornerOrient::AntiClockwise),
+            Corner::new(ULB, CornerOrient::Clockwise),
+            Corner::new(DBL, CornerOrient::AntiClockwise),
+        ]),
+        EdgePerm::new([
+            Edge::new(FR, EdgeOrient::Oriented),
+            Edge::new(DF, EdgeOrient::NotOriented),
+            Edge::new(FL, EdgeOrient::Oriented),
+            Edge::new(UF, EdgeOrient::NotOriented),
+            Edge::new(BR, EdgeOrient::Oriented),
+            Edge::new(UB, EdgeOrient::NotOriented),
+            Edge::new(BL, EdgeOrient::Oriented),
+            Edge::new(DB, EdgeOrient::NotOriented),
+            Edge::new(UR, EdgeOrient::NotOriented),
+            Edge::new(DR, EdgeOrient::NotOriented),
+            Edge::new(DL, EdgeOrient::NotOriented),
+            Edge::new(UL, EdgeOrient::NotOriented),
+        ]),
+    );
+
     /// Reflection about the M slice
     pub const M_REFL: Cube3Perm = Cube3Perm::new(
-        CornerPerm::new([
+        CornerPerm::new_mirrored([
             Corner::new(UBR, CornerOrient::Oriented),
             Corner::new(ULB, CornerOrient::Oriented),
             Corner::new(UFL, CornerOrient::Oriented),
@@ -169,3 +266,299 @@ mod primitives {
         ]),
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::coord::Coord;
+    use crate::cube::cube3::coord::{
+        CornerOrientCoord, CornerPosAndESliceCoord, CornerPosCoord, CornerPosSymCoord,
+        EEdgePosCoord, ESliceAndEOSymCoord, ESliceEdgePosCoord,
+    };
+    use crate::cube::cube3::{CubeTurn, G1CubeTurn};
+    use crate::move_table::{BasicMoveTable, CompositeMoveTable, MoveTable, SymMoveTable};
+    use crate::prune_table::{FullPruneTable, PruneTable, SymPruneTable};
+    use crate::symmetry::{conjugate, RepresentantTable};
+    use crate::table_io::{self, Header, LoadError};
+
+    make_symmetry_coord! {
+        struct ESliceSymCoord<SymmetryE, EEdgePosCoord>(ESliceClass(u16)): 45;
+    }
+
+    lazy_static! {
+        static ref REPRESENTANTS: RepresentantTable<Cube3Perm, ESliceSymCoord> =
+            RepresentantTable::create();
+    }
+
+    make_composite_coord! {
+        #[derive(Default)]
+        struct ESliceAndCOCoord<Cube3Perm>(u32) {
+            e_slice: EEdgePosCoord,
+            corner_orient: CornerOrientCoord,
+        }
+    }
+
+    impl Coord<Cube3Perm> for ESliceAndCOCoord {
+        const NAME: &'static str = "ESliceAndCOCoord";
+
+        fn from_perm(perm: &Cube3Perm) -> Self {
+            Self::new(
+                EEdgePosCoord::from_perm(perm),
+                CornerOrientCoord::from_perm(perm),
+            )
+        }
+
+        fn into_perm(self) -> Cube3Perm {
+            Cube3Perm::new(self.corner_orient().into_perm(), self.e_slice().into_perm())
+        }
+    }
+
+    impl Coord<Cube3Perm> for ESliceSymCoord {
+        const NAME: &'static str = "ESliceSymCoord";
+
+        fn from_perm(perm: &Cube3Perm) -> Self {
+            REPRESENTANTS.get_sym_coord(EEdgePosCoord::from_perm(perm))
+        }
+
+        fn into_perm(self) -> Cube3Perm {
+            REPRESENTANTS.get_base_coord(self).into_perm()
+        }
+    }
+
+    #[test]
+    fn full_symmetries_form_a_group() {
+        let perms = FullSymmetry::iter()
+            .map(|sym| sym.permutation().clone())
+            .collect::<Vec<_>>();
+        for (i, a) in perms.iter().enumerate() {
+            assert!(perms[i + 1..].iter().all(|b| a != b));
+        }
+
+        for a in FullSymmetry::iter() {
+            assert!(a
+                .permutation()
+                .sequence(a.invert().permutation())
+                .is_identity());
+            for b in FullSymmetry::iter() {
+                assert_eq!(
+                    a.sequence(&b).permutation(),
+                    &a.permutation().sequence(b.permutation()),
+                    "Bad sequence for ({:?}, {:?})",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn full_symmetries_conjugate_turns_into_turns() {
+        for sym in FullSymmetry::iter() {
+            for m in CubeTurn::iter() {
+                let conj = conjugate(&sym, m.permutation());
+                assert!(
+                    CubeTurn::iter().any(|n| n.permutation() == &conj),
+                    "Conjugating {:?} by {:?} is not a turn",
+                    m,
+                    sym
+                );
+            }
+        }
+
+        let urf_rot = FullSymmetry::new(1, SymmetryE::default());
+        assert_eq!(
+            &conjugate(&urf_rot, CubeTurn::U.permutation()),
+            CubeTurn::F.permutation()
+        );
+        assert_eq!(
+            &conjugate(&urf_rot, CubeTurn::R.permutation()),
+            CubeTurn::U.permutation()
+        );
+        assert_eq!(
+            &conjugate(&urf_rot, CubeTurn::F.permutation()),
+            CubeTurn::R.permutation()
+        );
+    }
+
+    #[test]
+    fn m_reflection_turns_r_into_l_prime() {
+        use CubeTurn::*;
+
+        let m_refl = SymmetryE::new(0, 0, 1);
+        let mirrored = [(U, UP), (R, LP), (F, FP), (D, DP), (L, RP), (B, BP)];
+        for (m, expected) in mirrored {
+            assert_eq!(
+                &conjugate(&m_refl, m.permutation()),
+                expected.permutation(),
+                "Bad reflection of {:?}",
+                m
+            );
+        }
+    }
+
+    #[test]
+    fn sym_move_table_matches_basic_move_table() {
+        let basic_table: BasicMoveTable<EEdgePosCoord, CubeTurn> = BasicMoveTable::create();
+        let sym_table: SymMoveTable<ESliceSymCoord, CubeTurn> =
+            SymMoveTable::create(&*REPRESENTANTS);
+
+        for coord in EEdgePosCoord::iter() {
+            let sym_coord = REPRESENTANTS.get_sym_coord(coord);
+            for m in CubeTurn::iter() {
+                assert_eq!(
+                    REPRESENTANTS.get_base_coord(sym_table.get_move(sym_coord, m)),
+                    basic_table.get_move(coord, m),
+                    "Different coordinates for input ({:?}, {:?})",
+                    coord,
+                    m
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sym_prune_table_matches_full_prune_table() {
+        let eslice_table: BasicMoveTable<EEdgePosCoord, CubeTurn> = BasicMoveTable::create();
+        let co_table: BasicMoveTable<CornerOrientCoord, CubeTurn> = BasicMoveTable::create();
+        let move_table =
+            CompositeMoveTable::<ESliceAndCOCoord, _, _>::new(&eslice_table, &co_table);
+
+        let full_table = FullPruneTable::create(&move_table, ESliceAndCOCoord::default());
+        let sym_table: SymPruneTable<_, ESliceSymCoord, ESliceAndCOCoord, CubeTurn> =
+            SymPruneTable::create(
+                REPRESENTANTS.clone(),
+                &eslice_table,
+                &co_table,
+                ESliceAndCOCoord::default(),
+            );
+
+        // Looking up all of them takes a while
+        for coord in ESliceAndCOCoord::iter().step_by(97) {
+            assert_eq!(
+                sym_table.get_min_moves(coord),
+                full_table.get_min_moves(coord),
+                "Different bounds for {:?}",
+                coord
+            );
+        }
+    }
+
+    #[test]
+    fn representant_table_matches_conjugation() {
+        for coord in EEdgePosCoord::iter() {
+            let (sym, equiv_class) = REPRESENTANTS.get_equiv_class(coord);
+            let representant = REPRESENTANTS.get_representant(equiv_class);
+            assert_eq!(
+                EEdgePosCoord::from_perm(&conjugate(&sym, &coord.into_perm())),
+                representant
+            );
+            assert!(representant <= coord);
+            assert_eq!(
+                REPRESENTANTS.get_base_coord(REPRESENTANTS.get_sym_coord(coord)),
+                coord
+            );
+        }
+    }
+
+    #[test]
+    fn phase2_sym_prune_table_matches_full_prune_table() {
+        let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let eslice_table: BasicMoveTable<ESliceEdgePosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let move_table =
+            CompositeMoveTable::<CornerPosAndESliceCoord, _, _>::new(&cp_table, &eslice_table);
+
+        let full_table = FullPruneTable::create(&move_table, CornerPosAndESliceCoord::default());
+        let sym_table: SymPruneTable<_, CornerPosSymCoord, CornerPosAndESliceCoord, G1CubeTurn> =
+            SymPruneTable::create(
+                RepresentantTable::create(),
+                &cp_table,
+                &eslice_table,
+                CornerPosAndESliceCoord::default(),
+            );
+
+        for coord in CornerPosAndESliceCoord::iter().step_by(31) {
+            assert_eq!(
+                sym_table.get_min_moves(coord),
+                full_table.get_min_moves(coord),
+                "Different bounds for {:?}",
+                coord
+            );
+        }
+    }
+
+    #[test]
+    fn representant_tables_have_known_class_counts() {
+        assert_eq!(REPRESENTANTS.len(), 45);
+        assert_eq!(
+            RepresentantTable::<Cube3Perm, CornerPosSymCoord>::create().len(),
+            2_768
+        );
+        assert_eq!(
+            RepresentantTable::<Cube3Perm, ESliceAndEOSymCoord>::create().len(),
+            64_430
+        );
+    }
+
+    #[test]
+    fn class_symmetries_fix_representants() {
+        let mut class_sizes = 0;
+        for equiv_class in ESliceClass::iter() {
+            let representant = REPRESENTANTS.get_representant(equiv_class);
+            let perm = representant.into_perm();
+            let class_syms = REPRESENTANTS
+                .get_class_symmetries(equiv_class)
+                .collect::<Vec<_>>();
+
+            assert!(class_syms.contains(&SymmetryE::default()));
+            for sym in SymmetryE::iter() {
+                assert_eq!(
+                    class_syms.contains(&sym),
+                    EEdgePosCoord::from_perm(&conjugate(&sym, &perm)) == representant,
+                    "Wrong class symmetry {:?} for {:?}",
+                    sym,
+                    representant
+                );
+            }
+
+            // Each class has one coordinate per coset of the class symmetries
+            class_sizes += SymmetryE::COUNT / class_syms.len();
+        }
+
+        assert_eq!(class_sizes, EEdgePosCoord::COUNT);
+    }
+
+    #[test]
+    fn representant_table_round_trip() {
+        let mut buf = Vec::new();
+        REPRESENTANTS.save(&mut buf).unwrap();
+
+        let loaded: RepresentantTable<Cube3Perm, ESliceSymCoord> =
+            RepresentantTable::load(buf.as_slice()).unwrap();
+        assert!(loaded.iter().eq(REPRESENTANTS.iter()));
+        for coord in EEdgePosCoord::iter() {
+            assert_eq!(
+                loaded.get_sym_coord(coord),
+                REPRESENTANTS.get_sym_coord(coord)
+            );
+        }
+    }
+
+    #[test]
+    fn representant_table_load_fails_for_invalid_entry() {
+        let mut buf = Vec::new();
+        REPRESENTANTS.save(&mut buf).unwrap();
+
+        // Replace the representant of the second class, and write the entries back with a valid
+        // checksum
+        let mut entries = buf[buf.len() - REPRESENTANTS.len() * 12..].to_vec();
+        let last_coord = EEdgePosCoord::COUNT as u32 - 1;
+        entries[12..16].copy_from_slice(&last_coord.to_le_bytes());
+        let header = Header::representant::<Cube3Perm, ESliceSymCoord>(REPRESENTANTS.len());
+        let mut tampered = Vec::new();
+        table_io::write_table(&mut tampered, &header, &entries).unwrap();
+
+        let result = RepresentantTable::<Cube3Perm, ESliceSymCoord>::load(tampered.as_slice());
+        assert!(matches!(result, Err(LoadError::InvalidEntry(1))));
+    }
+}
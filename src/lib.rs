@@ -4,10 +4,14 @@ extern crate strum;
 #[macro_use]
 extern crate num_derive;
 
+extern crate memmap2;
+
 extern crate num_traits;
 
 extern crate num_integer;
 
+extern crate rayon;
+
 extern crate termion;
 
 #[macro_use]
@@ -26,6 +30,7 @@ pub mod move_table;
 pub mod notation;
 pub mod prune_table;
 pub mod solver;
+pub mod table_io;
 #[macro_use]
 pub mod symmetry;
 pub mod cube;
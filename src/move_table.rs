@@ -4,12 +4,18 @@
 //! This module allows constructing them automatically based on the `Coord` and
 //! `PuzzleMove` implementations.
 
+use std::io::{self, Read, Write};
 use std::marker::PhantomData;
 
+use num_traits::FromPrimitive;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
 use crate::coord::{CompositeCoord, Coord};
 use crate::puzzle::{PuzzleMove, PuzzlePerm};
-use crate::symmetry::{SymCoord, Symmetry};
-use crate::util::{EnumCount, IntoEnumIterator};
+use crate::symmetry::{RepresentantTable, SymCoord};
+use crate::table_io::{self, Header, LoadError, TableKind};
+use crate::util::{self, EnumCount, EnumIndex, IntoEnumIterator};
 
 pub trait MoveTable {
     type Puzzle: PuzzlePerm;
@@ -31,6 +37,22 @@ where
 {
     /// Create and fill a move table
     pub fn create() -> Self {
+        Self::create_with(|perm, mov| perm.sequence(mov.permutation()))
+    }
+
+    /// Create and fill a move table like `create`, splitting the coordinates between threads.
+    ///
+    /// The work is done in `pool` if given, or in the global thread pool otherwise. The resulting
+    /// table is the same as the one from `create`.
+    pub fn create_parallel(pool: Option<&ThreadPool>) -> Self
+    where
+        C: FromPrimitive + Send,
+    {
+        Self::create_parallel_with(pool, |perm, mov| perm.sequence(mov.permutation()))
+    }
+
+    /// Fill a table with the coordinate of `apply` for each coordinate and move.
+    fn create_with(apply: impl Fn(&M::Puzzle, M) -> M::Puzzle) -> Self {
         let mut table = Vec::with_capacity(C::COUNT * M::COUNT);
 
         for coord in C::iter() {
@@ -38,8 +60,7 @@ where
 
             // Apply each of the moves and add resulting coordinate to the table
             for mov in M::iter() {
-                let new_perm = perm.sequence(mov.permutation());
-                table.push(C::from_perm(&new_perm));
+                table.push(C::from_perm(&apply(&perm, mov)));
             }
         }
 
@@ -48,9 +69,77 @@ where
             // Is that worth doing anyway, since we would have to deal with
             // initializing all of the elements?
             table: table.into_boxed_slice(),
-            _moves: PhantomData::default(),
+            _moves: PhantomData,
+        }
+    }
+
+    fn create_parallel_with(
+        pool: Option<&ThreadPool>,
+        apply: impl Fn(&M::Puzzle, M) -> M::Puzzle + Sync,
+    ) -> Self
+    where
+        C: FromPrimitive + Send,
+    {
+        let table = util::install(pool, || {
+            (0..C::COUNT)
+                .into_par_iter()
+                .flat_map_iter(|index| {
+                    let perm = C::from_usize(index).unwrap().into_perm();
+
+                    // Apply each of the moves and add resulting coordinate to the table
+                    let apply = &apply;
+                    M::iter().map(move |mov| C::from_perm(&apply(&perm, mov)))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            table: table.into_boxed_slice(),
+            _moves: PhantomData,
         }
     }
+
+    /// Write the table in the format described in `table_io`.
+    pub fn save<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.save_as(TableKind::Move, writer)
+    }
+
+    /// Read a table written by `save`.
+    pub fn load<R: Read>(reader: R) -> Result<Self, LoadError>
+    where
+        C: FromPrimitive,
+    {
+        Self::load_as(TableKind::Move, reader)
+    }
+
+    fn save_as<W: Write>(&self, kind: TableKind, mut writer: W) -> io::Result<()> {
+        let mut entries = Vec::with_capacity(self.table.len() * 4);
+        for coord in self.table.iter() {
+            entries.extend_from_slice(&(coord.index() as u32).to_le_bytes());
+        }
+
+        let header = Header::new::<C, M>(kind, self.table.len());
+        table_io::write_table(&mut writer, &header, &entries)
+    }
+
+    fn load_as<R: Read>(kind: TableKind, mut reader: R) -> Result<Self, LoadError>
+    where
+        C: FromPrimitive,
+    {
+        let header = Header::new::<C, M>(kind, C::COUNT * M::COUNT);
+        let entries = table_io::read_table(&mut reader, &header, 4)?;
+
+        let table = entries
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(index, bytes)| table_io::decode_coord(bytes, index))
+            .collect::<Result<Vec<C>, _>>()?;
+
+        Ok(Self {
+            table: table.into_boxed_slice(),
+            _moves: PhantomData,
+        })
+    }
 }
 
 impl<C, M> MoveTable for BasicMoveTable<C, M>
@@ -67,6 +156,78 @@ where
     }
 }
 
+/// Move table for the inverse of a state.
+///
+/// Applying a move after a state applies its inverse before the inverse state, so the entry for a
+/// coordinate and a move is the coordinate of the inverse move followed by the coordinate's
+/// permutation. This lets a search keep track of the inverse state's coordinate along with the
+/// regular one (see `SolutionIter::with_inverse_lookups`).
+///
+/// Moving before a state only depends on its coordinate if the coordinate covers a whole group,
+/// such as all of the corners, so this is only meaningful for those coordinates. For that reason,
+/// this can't be built from the tables for sub-coordinates like `CompositeMoveTable` is.
+pub struct InverseMoveTable<C, M>(BasicMoveTable<C, M>);
+
+impl<C, M> InverseMoveTable<C, M>
+where
+    C: Coord<M::Puzzle>,
+    M: PuzzleMove,
+{
+    /// Create and fill an inverse move table.
+    pub fn create() -> Self {
+        let inverses = inverse_moves::<M>();
+        Self(BasicMoveTable::create_with(|perm, mov: M| {
+            inverses[mov.index()].sequence(perm)
+        }))
+    }
+
+    /// Create and fill an inverse move table like `create`, splitting the coordinates between
+    /// threads.
+    pub fn create_parallel(pool: Option<&ThreadPool>) -> Self
+    where
+        C: FromPrimitive + Send,
+        M::Puzzle: Sync,
+    {
+        let inverses = inverse_moves::<M>();
+        Self(BasicMoveTable::create_parallel_with(
+            pool,
+            |perm, mov: M| inverses[mov.index()].sequence(perm),
+        ))
+    }
+
+    /// Write the table in the format described in `table_io`.
+    pub fn save<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.0.save_as(TableKind::InverseMove, writer)
+    }
+
+    /// Read a table written by `save`.
+    pub fn load<R: Read>(reader: R) -> Result<Self, LoadError>
+    where
+        C: FromPrimitive,
+    {
+        BasicMoveTable::load_as(TableKind::InverseMove, reader).map(Self)
+    }
+}
+
+/// Inverses of the moves, indexed by the moves.
+fn inverse_moves<M: PuzzleMove>() -> Vec<M::Puzzle> {
+    M::iter().map(|m| m.permutation().invert()).collect()
+}
+
+impl<C, M> MoveTable for InverseMoveTable<C, M>
+where
+    C: Coord<M::Puzzle>,
+    M: PuzzleMove,
+{
+    type Puzzle = M::Puzzle;
+    type Coord = C;
+    type Move = M;
+
+    fn get_move(&self, coord: C, mov: M) -> C {
+        self.0.get_move(coord, mov)
+    }
+}
+
 /// Move table for composite coordinates which uses a separate table for each sub-coordinate.
 ///
 /// This can be used as-is, or it can be used as a more efficient way of constructing a
@@ -83,7 +244,7 @@ impl<'a, C, AT, BT> CompositeMoveTable<'a, C, AT, BT> {
         CompositeMoveTable {
             table_a,
             table_b,
-            _coord: PhantomData::default(),
+            _coord: PhantomData,
         }
     }
 }
@@ -111,7 +272,35 @@ where
 
         BasicMoveTable {
             table: table.into_boxed_slice(),
-            _moves: PhantomData::default(),
+            _moves: PhantomData,
+        }
+    }
+
+    /// Build a full / flattened move table like `to_basic`, splitting the coordinates between
+    /// threads.
+    ///
+    /// The work is done in `pool` if given, or in the global thread pool otherwise.
+    pub fn to_basic_parallel(&self, pool: Option<&ThreadPool>) -> BasicMoveTable<C, M>
+    where
+        AT: Sync,
+        BT: Sync,
+        C: FromPrimitive + Send + Sync,
+    {
+        let table = util::install(pool, || {
+            (0..C::COUNT)
+                .into_par_iter()
+                .flat_map_iter(|index| {
+                    let coord = C::from_usize(index).unwrap();
+
+                    // Apply each of the moves and add resulting coordinate to the table
+                    M::iter().map(move |mov| self.get_move(coord, mov))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        BasicMoveTable {
+            table: table.into_boxed_slice(),
+            _moves: PhantomData,
         }
     }
 }
@@ -136,6 +325,11 @@ where
     }
 }
 
+/// Move table for a symmetry-reduced coordinate.
+///
+/// Only the moves applied to the representant of each equivalence class are stored. To apply a
+/// move to some other coordinate, the move is conjugated by the coordinate's symmetry, applied to
+/// the representant of its class, and the resulting symmetry is combined with the original one.
 pub struct SymMoveTable<C, M> {
     /// Mapping from (EquivClass, M) -> C (equiv class and symmetry)
     coord_table: Box<[C]>,
@@ -144,13 +338,13 @@ pub struct SymMoveTable<C, M> {
     move_table: Box<[M]>,
 }
 
-impl<C, M> SymMoveTable<C, M>
+impl<P, C, M> SymMoveTable<C, M>
 where
-    C: SymCoord<M::Puzzle> + Coord<M::Puzzle>,
-    M: PuzzleMove,
+    P: PuzzlePerm,
+    C: SymCoord<P>,
+    M: PuzzleMove<Puzzle = P>,
 {
-    pub fn create() -> Self {
-        let representants = C::representants();
+    pub fn create(representants: &RepresentantTable<P, C>) -> Self {
         let mut coord_table = Vec::with_capacity(representants.len() * M::COUNT);
 
         for representant in representants.iter() {
@@ -159,7 +353,7 @@ where
             // Apply each of the moves and add resulting coordinate to the table
             for mov in M::iter() {
                 let new_perm = perm.sequence(mov.permutation());
-                coord_table.push(C::from_perm(&new_perm));
+                coord_table.push(representants.get_sym_coord(C::BaseCoord::from_perm(&new_perm)));
             }
         }
 
@@ -168,13 +362,16 @@ where
             let perm = mov.permutation();
 
             for sym in C::Symmetry::iter() {
-                let transformed = perm.sequence(sym.permutation());
+                let transformed = sym
+                    .permutation()
+                    .sequence(perm)
+                    .sequence(sym.invert().permutation());
                 // Find the move with the right permutation
                 if let Some(transformed_move) = M::iter().find(|m| *m.permutation() == transformed)
                 {
                     move_table.push(transformed_move);
                 } else {
-                    panic!("Transformed move coult not be found.");
+                    panic!("Transformed move could not be found.");
                 }
             }
         }
@@ -189,17 +386,86 @@ where
     }
 }
 
-impl<C, M> MoveTable for SymMoveTable<C, M>
+impl<P, C, M> MoveTable for SymMoveTable<C, M>
 where
-    C: SymCoord<M::Puzzle> + Coord<M::Puzzle>,
-    M: PuzzleMove,
+    P: PuzzlePerm,
+    C: SymCoord<P> + Coord<P>,
+    M: PuzzleMove<Puzzle = P>,
 {
-    type Puzzle = M::Puzzle;
+    type Puzzle = P;
     type Coord = C;
     type Move = M;
 
     fn get_move(&self, coord: C, mov: M) -> C {
+        // If the coordinate is `S^-1 R S` for the representant `R`, then applying `M` gives
+        // `S^-1 (R (S M S^-1)) S`, so the move is conjugated and applied to the representant.
+        let sym = coord.symmetry();
+        let conj_move = self.move_table[mov.index() * C::Symmetry::COUNT + sym.index()];
+        let new_coord =
+            self.coord_table[coord.equiv_class().index() * M::COUNT + conj_move.index()];
+
+        C::from_sym_and_class(new_coord.symmetry().sequence(&sym), new_coord.equiv_class())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
 
+    use crate::cube::cube3::coord::{CornerPosAndESliceCoord, CornerPosCoord, ESliceEdgePosCoord};
+    use crate::cube::cube3::{Cube3Perm, CubeTurn, G1CubeTurn};
+    use rayon::ThreadPoolBuilder;
+
+    fn assert_same_table<C: Coord<M::Puzzle>, M: PuzzleMove>(
+        a: &BasicMoveTable<C, M>,
+        b: &BasicMoveTable<C, M>,
+    ) {
+        assert!(a.table == b.table, "Tables are different");
+    }
+
+    #[test]
+    fn parallel_basic_table_matches_sequential() {
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+
+        let sequential: BasicMoveTable<CornerPosCoord, CubeTurn> = BasicMoveTable::create();
+        assert_same_table(&BasicMoveTable::create_parallel(Some(&pool)), &sequential);
+        assert_same_table(&BasicMoveTable::create_parallel(None), &sequential);
+    }
+
+    #[test]
+    fn parallel_composite_table_matches_sequential() {
+        let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let eslice_table: BasicMoveTable<ESliceEdgePosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let composite_table =
+            CompositeMoveTable::<CornerPosAndESliceCoord, _, _>::new(&cp_table, &eslice_table);
+
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        assert_same_table(
+            &composite_table.to_basic_parallel(Some(&pool)),
+            &composite_table.to_basic(),
+        );
+    }
+
+    #[test]
+    fn inverse_table_follows_inverse_states() {
+        let inverse_table: InverseMoveTable<CornerPosCoord, CubeTurn> = InverseMoveTable::create();
+        assert_same_table(&InverseMoveTable::create_parallel(None).0, &inverse_table.0);
+
+        let mut perm = Cube3Perm::identity().clone();
+        for mov in [
+            CubeTurn::R,
+            CubeTurn::U2,
+            CubeTurn::FP,
+            CubeTurn::L,
+            CubeTurn::D,
+        ] {
+            let inverse = CornerPosCoord::from_perm(&perm.invert());
+            perm = perm.sequence(mov.permutation());
+            assert_eq!(
+                inverse_table.get_move(inverse, mov),
+                CornerPosCoord::from_perm(&perm.invert())
+            );
+        }
     }
 }
 
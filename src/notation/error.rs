@@ -0,0 +1,108 @@
+use std::error::Error;
+use std::fmt;
+
+/// Reason a notation string could not be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotationErrorReason {
+    /// The move is not a primitive of the notation.
+    UnknownPrim,
+
+    /// A move is repeated 0 times.
+    ZeroCount,
+
+    /// A move count is too large to be stored.
+    CountOverflow,
+
+    /// There is extra text after a move.
+    TrailingInput,
+
+    /// A bracket or parenthesis is never closed.
+    UnclosedBracket,
+
+    /// A bracket, parenthesis or separator is in the wrong place.
+    UnexpectedDelimiter,
+
+    /// A block comment is never closed.
+    UnclosedComment,
+}
+
+impl fmt::Display for NotationErrorReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            NotationErrorReason::UnknownPrim => "unknown move",
+            NotationErrorReason::ZeroCount => "move count cannot be 0",
+            NotationErrorReason::CountOverflow => "move count is too large",
+            NotationErrorReason::TrailingInput => "unexpected text after move",
+            NotationErrorReason::UnclosedBracket => "unclosed bracket",
+            NotationErrorReason::UnexpectedDelimiter => "unexpected delimiter",
+            NotationErrorReason::UnclosedComment => "unclosed comment",
+        })
+    }
+}
+
+/// Error when parsing puzzle notation.
+///
+/// This keeps a copy of the input so that it can be displayed with a caret under the offending
+/// token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotationError {
+    input: String,
+    offset: usize,
+    len: usize,
+    reason: NotationErrorReason,
+}
+
+impl NotationError {
+    /// Create an error for the `len` bytes of `input` starting at `offset`.
+    pub(crate) fn new(input: &str, offset: usize, len: usize, reason: NotationErrorReason) -> Self {
+        NotationError {
+            input: input.to_string(),
+            offset,
+            len,
+            reason,
+        }
+    }
+
+    /// Byte offset of the offending token in the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The offending token.
+    pub fn token(&self) -> &str {
+        &self.input[self.offset..self.offset + self.len]
+    }
+
+    pub fn reason(&self) -> NotationErrorReason {
+        self.reason
+    }
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} \"{}\" at offset {}",
+            self.reason,
+            self.token(),
+            self.offset
+        )?;
+
+        // Only show the line containing the error
+        let line_start = self.input[..self.offset]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let line_end = self.input[self.offset..]
+            .find('\n')
+            .map_or(self.input.len(), |index| self.offset + index);
+        let line = self.input[line_start..line_end].trim_end();
+
+        // Count characters instead of bytes so that the caret lines up
+        let column = self.input[line_start..self.offset].chars().count();
+        let width = self.token().chars().count().max(1);
+        writeln!(f, "{}", line)?;
+        write!(f, "{:column$}{}", "", "^".repeat(width), column = column)
+    }
+}
+
+impl Error for NotationError {}
@@ -1,23 +1,100 @@
 //! 2x2x2 cube puzzle notation
 
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use num_traits::PrimInt;
 
 use crate::puzzle::PuzzlePerm;
+use crate::util::IntoEnumIterator;
 
+mod error;
 // These just implement Display and FromStr, they don't have exports
 mod parser;
 mod printer;
+mod tree;
+
+pub use error::{NotationError, NotationErrorReason};
+pub use tree::{NotationComment, NotationNode, NotationStep, NotationTree};
 
 /// Trait for primitive moves in a puzzle notation.
-pub trait NotationPrim: Copy + Eq + FromStr + ToString {
+///
+/// The ordering of primitives is used as the canonical order for moves which commute.
+pub trait NotationPrim: Copy + Ord + FromStr + ToString {
     type Puzzle: PuzzlePerm;
 
+    /// Permutation relative to the pieces which define the puzzle's frame (e.g. the centers of a
+    /// cube).
     fn permutation(&self) -> &Self::Puzzle;
+
+    /// Rotation of the whole puzzle done along with the permutation.
+    ///
+    /// Later moves are made in the rotated frame. This must commute with `permutation`.
+    fn rotation(&self) -> Option<&Self::Puzzle> {
+        None
+    }
+
+    /// Number of times the primitive has to be applied to get back to the identity.
+    ///
+    /// By default, this is computed from the permutation and rotation.
+    fn order(&self) -> u8 {
+        let identity = FramedPerm::identity();
+        let mut power = identity.apply_prim(self);
+        let mut order = 1;
+        while power != identity {
+            power = power.apply_prim(self);
+            order += 1;
+        }
+        order
+    }
+
+    /// Whether applying the primitives in either order has the same effect.
+    ///
+    /// By default, this is computed from the permutations and rotations.
+    fn commutes_with(&self, other: &Self) -> bool {
+        let identity = FramedPerm::identity();
+        identity.apply_prim(self).apply_prim(other) == identity.apply_prim(other).apply_prim(self)
+    }
+}
+
+/// Permutation of a puzzle along with the rotation of the frame which moves are made in.
+///
+/// The whole puzzle is the permutation followed by the rotation, so a move `m` made in the
+/// rotated frame is `frame * m * frame^-1` relative to the puzzle's fixed pieces.
+#[derive(Clone, Debug, PartialEq)]
+struct FramedPerm<P> {
+    perm: P,
+    frame: P,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+impl<P: PuzzlePerm> FramedPerm<P> {
+    fn identity() -> Self {
+        FramedPerm {
+            perm: P::identity().clone(),
+            frame: P::identity().clone(),
+        }
+    }
+
+    fn apply(&self, perm: &P, rotation: Option<&P>) -> Self {
+        let perm = if self.frame.is_identity() {
+            self.perm.sequence(perm)
+        } else {
+            self.perm
+                .sequence(&self.frame.sequence(perm).sequence(&self.frame.invert()))
+        };
+        let frame = match rotation {
+            Some(rotation) => self.frame.sequence(rotation),
+            None => self.frame.clone(),
+        };
+        FramedPerm { perm, frame }
+    }
+
+    fn apply_prim<M: NotationPrim<Puzzle = P>>(&self, prim: &M) -> Self {
+        self.apply(prim.permutation(), prim.rotation())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct NotationMove<M: NotationPrim> {
     prim: M,
     count: i8,
@@ -39,11 +116,50 @@ impl<M: NotationPrim> NotationMove<M> {
         Self::ntimes(prim, -1)
     }
 
+    /// The move which undoes this one.
+    pub fn invert(&self) -> Self {
+        Self::ntimes(self.prim, -self.count)
+    }
+
     pub fn permutation(&self) -> M::Puzzle {
         self.prim.permutation().ntimes(self.count)
     }
 
-    /* TODO Add back simplification and cannonicalization?
+    pub fn rotation(&self) -> Option<M::Puzzle> {
+        self.prim
+            .rotation()
+            .map(|rotation| rotation.ntimes(self.count))
+    }
+
+    pub fn prim(&self) -> M {
+        self.prim
+    }
+
+    pub fn count(&self) -> i8 {
+        self.count
+    }
+
+    /// Reduces the count modulo the order of the primitive, preferring inverses for counts above
+    /// half of the order (e.g. `R3` becomes `R'` and `R2'` becomes `R2`).
+    ///
+    /// Returns `None` if the move has no effect.
+    pub fn reduced(&self) -> Option<Self> {
+        Self::reduced_count(self.prim, self.count as i16)
+    }
+
+    fn reduced_count(prim: M, count: i16) -> Option<Self> {
+        Self::reduced_count_with_order(prim, count, prim.order())
+    }
+
+    fn reduced_count_with_order(prim: M, count: i16, order: u8) -> Option<Self> {
+        let order = order as i16;
+        let count = count.rem_euclid(order);
+        match count {
+            0 => None,
+            count if count > order / 2 => Some(Self::ntimes(prim, count - order)),
+            count => Some(Self::ntimes(prim, count)),
+        }
+    }
 
     /// Tries to combine this move with another one.
     ///
@@ -54,20 +170,21 @@ impl<M: NotationPrim> NotationMove<M> {
     /// - The moves cancel (`Some(None)`)
     pub fn combine_with(&self, other: &Self) -> Option<Option<Self>> {
         if self.prim == other.prim {
-            let new_count = self.count + other.count;
-            if new_count == 0 {
-                Some(None)
-            } else {
-                Some(Some(Self::ntimes(self.prim, new_count)))
-            }
+            Some(Self::reduced_count(
+                self.prim,
+                self.count as i16 + other.count as i16,
+            ))
         } else {
             None
         }
     }
-    */
+
+    pub fn commutes_with(&self, other: &Self) -> bool {
+        self.prim.commutes_with(&other.prim)
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct NotationStr<M: NotationPrim> {
     moves: Vec<NotationMove<M>>,
 }
@@ -93,178 +210,257 @@ impl<M: NotationPrim> NotationStr<M> {
         self.moves.is_empty()
     }
 
+    pub fn moves(&self) -> &[NotationMove<M>] {
+        &self.moves
+    }
+
+    /// The moves which undo these ones.
+    pub fn invert(&self) -> Self {
+        NotationStr {
+            moves: self.moves.iter().rev().map(NotationMove::invert).collect(),
+        }
+    }
+
+    fn framed_permutation(&self) -> FramedPerm<M::Puzzle> {
+        self.moves.iter().fold(FramedPerm::identity(), |state, m| {
+            state.apply(&m.permutation(), m.rotation().as_ref())
+        })
+    }
+
+    /// Permutation of the puzzle relative to the pieces which define its frame.
+    ///
+    /// Rotations are not included, but moves after them are made in the rotated frame.
     pub fn permutation(&self) -> M::Puzzle {
-        self.moves
+        self.framed_permutation().perm
+    }
+
+    /// Rotation of the whole puzzle after all of the moves.
+    pub fn frame(&self) -> M::Puzzle {
+        self.framed_permutation().frame
+    }
+
+    /// Rewrites moves relative to the puzzle's frame (e.g. solver output) for a puzzle which has
+    /// been rotated by `frame`.
+    ///
+    /// Applying the result after a scramble has the same effect as applying the original moves
+    /// relative to the scramble's `permutation`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a rotated move is not a primitive of the notation.
+    pub fn reframe(&self, frame: &M::Puzzle) -> Self
+    where
+        M: IntoEnumIterator,
+    {
+        let inverse = frame.invert();
+        let rotate = |perm: &M::Puzzle| inverse.sequence(perm).sequence(frame);
+
+        let moves = self
+            .moves
             .iter()
-            .fold(M::Puzzle::identity().clone(), |perm, m| {
-                perm.sequence(&m.permutation())
+            .map(|m| {
+                let perm = rotate(&m.permutation());
+                let rotation = m.rotation().map(|rotation| rotate(&rotation));
+                M::iter()
+                    .flat_map(|prim| {
+                        [
+                            NotationMove::ntimes(prim, m.count),
+                            NotationMove::ntimes(prim, -m.count),
+                        ]
+                    })
+                    .find(|candidate| {
+                        candidate.permutation() == perm && candidate.rotation() == rotation
+                    })
+                    .expect("Rotated move is not a primitive")
             })
+            .collect();
+
+        NotationStr { moves }
     }
 
-    /*
+    /// Simplifies the moves into a canonical form.
+    ///
+    /// Moves of the same primitive which are only separated by commuting moves are combined (or
+    /// cancelled), counts are reduced modulo the primitive's order, and runs of commuting moves are
+    /// sorted by primitive.
     pub fn normalize(&self) -> Self {
-        // This will generally not be much smaller than the original (and can't
-        // be larger)
-        let mut moves = Vec::with_capacity(self.moves.len());
-        // Buffer to combine and order commutative moves before pushing them
-        // into the main move list. In most "real-world" cases, this will only
-        // store a single move.
-        let mut buffer = Vec::with_capacity(1);
-
-        for m in &self.moves {
-            // Look for a move it combines with and the index of that move
-            let combined = buffer
-                .iter()
-                .enumerate()
-                .find_map(|(index, bm)| m.combine_with(bm).map(|combined| (index, combined)));
-            match combined {
-                Some((index, Some(single_move))) => {
-                    // Replace with the single move
-                    buffer[index] = single_move;
-                }
-                Some((index, None)) => {
-                    // Remove the original since it canceled with the new move
-                    buffer.remove(index);
-                }
-                None => {
-                    // If the new move doesn't commute with everything in the
-                    // buffer, commit the moves in the buffer
-                    if !buffer.iter().all(|bm| m.commutes_with(bm)) {
-                        buffer.sort_unstable();
-                        moves.append(&mut buffer); // Also clears the buffer
+        // This will generally not be much smaller than the original (and can't be larger)
+        let mut moves: Vec<NotationMove<M>> = Vec::with_capacity(self.moves.len());
+        let mut cache = PrimCache::new();
+
+        for m in self.moves.iter() {
+            let m = match NotationMove::reduced_count_with_order(
+                m.prim,
+                m.count as i16,
+                cache.order(m.prim),
+            ) {
+                Some(m) => m,
+                None => continue,
+            };
+
+            // Look back through the moves which commute with the new one, since it could be moved
+            // to any of those positions
+            let mut start = moves.len();
+            let mut combined = false;
+            while start > 0 {
+                let prev = moves[start - 1];
+                if prev.prim == m.prim {
+                    // Same as `combine_with`
+                    let count = prev.count as i16 + m.count as i16;
+                    let order = cache.order(m.prim);
+                    match NotationMove::reduced_count_with_order(m.prim, count, order) {
+                        Some(single_move) => moves[start - 1] = single_move,
+                        None => {
+                            moves.remove(start - 1);
+                        }
                     }
-
-                    buffer.push(*m);
+                    combined = true;
+                    break;
+                } else if cache.commutes(prev.prim, m.prim) {
+                    start -= 1;
+                } else {
+                    break;
                 }
             }
+
+            if !combined {
+                // Keep the commuting moves at the end sorted
+                let index = moves[start..]
+                    .iter()
+                    .position(|prev| prev.prim > m.prim)
+                    .map_or(moves.len(), |offset| start + offset);
+                moves.insert(index, m);
+            }
         }
 
-        buffer.sort_unstable();
-        moves.append(&mut buffer);
         moves.shrink_to_fit();
-        NotationStr { moves: moves }
+        NotationStr { moves }
     }
-    */
 }
 
-/* TODO Implement better mock datatype
-#[cfg(test)]
-pub(crate) mod test {
-    use super::*;
+/// Orders of primitives and whether they commute, computed at most once for each primitive or
+/// pair.
+///
+/// The defaults in `NotationPrim` are computed from the permutations, which is slow to do for every
+/// pair of moves.
+struct PrimCache<M> {
+    orders: BTreeMap<M, u8>,
+    commutes: BTreeMap<(M, M), bool>,
+}
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-    pub enum TestPrim {
-        A,
-        B,
-        C,
-        D,
-    }
-
-    impl FromStr for TestPrim {
-        type Err = ();
-
-        fn from_str(s: &str) -> Result<Self, ()> {
-            use TestPrim::*;
-            match s {
-                "A" => Ok(A),
-                "B" => Ok(B),
-                "C" => Ok(C),
-                "D" => Ok(D),
-                _ => Err(()),
-            }
+impl<M: NotationPrim> PrimCache<M> {
+    fn new() -> Self {
+        PrimCache {
+            orders: BTreeMap::new(),
+            commutes: BTreeMap::new(),
         }
     }
 
-    impl ToString for TestPrim {
-        fn to_string(&self) -> String {
-            use TestPrim::*;
-            match self {
-                A => "A",
-                B => "B",
-                C => "C",
-                D => "D",
-            }
-            .to_string()
-        }
+    fn order(&mut self, prim: M) -> u8 {
+        *self.orders.entry(prim).or_insert_with(|| prim.order())
     }
 
-    impl NotationPrim for TestPrim {
-        fn permutation(&self) -> &PuzzlePerm {
+    fn commutes(&mut self, a: M, b: M) -> bool {
+        // Commuting is symmetric, so only store one order of the pair
+        let key = if a <= b { (a, b) } else { (b, a) };
+        *self
+            .commutes
+            .entry(key)
+            .or_insert_with(|| a.commutes_with(&b))
+    }
+}
 
-        }
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::cube::cube3::notation::Cube3Notation::{self, *};
+
+    fn notation(s: &str) -> NotationStr<Cube3Notation> {
+        s.parse().unwrap()
     }
 
-    /*
+    #[test]
+    fn cube_prims_have_order_4() {
+        assert!([U, R, F, D, L, B].iter().all(|prim| prim.order() == 4));
+    }
 
-    /// Macro for building `NotationStr`s.
-    ///
-    /// Moves are separated by commas and the following prefixes can be used:
-    /// - `-`: Inverts the primitive move
-    /// - `2`: Doubles the primitive move
-    #[macro_export]
-    macro_rules! notation {
-        (@accum () -> ($($acc:tt)*)) => {NotationStr::from(vec![ $($acc)* ])};
-
-        // TODO Is there a way to support an optional trailing comma without
-        // repeating the rules?
-
-        (@accum (- $m:expr) -> ($($acc:tt)*)) => {
-            notation!(@accum () -> ($($acc)* NotationMove::inverse($m),))
-        };
-        (@accum ($n:literal $m:expr) -> ($($acc:tt)*)) => {
-            notation!(@accum () -> ($($acc)* NotationMove::ntimes($m, $n),))
-        };
-        (@accum ($m:expr) -> ($($acc:tt)*)) => {
-            notation!(@accum () -> ($($acc)* NotationMove::basic($m),))
-        };
+    #[test]
+    fn opposite_faces_commute() {
+        assert!(U.commutes_with(&D));
+        assert!(L.commutes_with(&R));
+        assert!(R.commutes_with(&R));
+        assert!(!U.commutes_with(&R));
+        assert!(!F.commutes_with(&L));
+    }
 
-        (@accum (- $m:expr, $($rest:tt)*) -> ($($acc:tt)*)) => {
-            notation!(@accum ($($rest)*) -> ($($acc)* NotationMove::inverse($m),))
-        };
-        (@accum ($n:literal $m:expr, $($rest:tt)*) -> ($($acc:tt)*)) => {
-            notation!(@accum ($($rest)*) -> ($($acc)* NotationMove::ntimes($m, $n),))
-        };
-        (@accum ($m:expr, $($rest:tt)*) -> ($($acc:tt)*)) => {
-            notation!(@accum ($($rest)*) -> ($($acc)* NotationMove::basic($m),))
-        };
+    #[test]
+    fn reduces_counts_modulo_order() {
+        assert_eq!(
+            NotationMove::ntimes(R, 3).reduced(),
+            Some(NotationMove::inverse(R))
+        );
+        assert_eq!(
+            NotationMove::ntimes(R, -2).reduced(),
+            Some(NotationMove::ntimes(R, 2))
+        );
+        assert_eq!(
+            NotationMove::ntimes(R, 5).reduced(),
+            Some(NotationMove::basic(R))
+        );
+        assert_eq!(NotationMove::ntimes(R, -4).reduced(), None);
+    }
 
-        ($($code:tt)*) => {notation!(@accum ($($code)*) -> ())};
+    #[test]
+    fn combines_same_prim() {
+        let r = NotationMove::basic(R);
+        assert_eq!(r.combine_with(&r), Some(Some(NotationMove::ntimes(R, 2))));
+        assert_eq!(r.combine_with(&NotationMove::inverse(R)), Some(None));
+        assert_eq!(r.combine_with(&NotationMove::basic(L)), None);
     }
 
     #[test]
     fn normalize_orders_commutative_moves() {
-        assert_eq!(notation!(A, B).normalize(), notation!(A, B));
-        assert_eq!(notation!(D, C).normalize(), notation!(C, D));
+        assert_eq!(notation("U D").normalize(), notation("U D"));
+        assert_eq!(notation("D U").normalize(), notation("U D"));
+        assert_eq!(notation("R L' U").normalize(), notation("R L' U"));
+        assert_eq!(notation("L' R U").normalize(), notation("R L' U"));
+    }
+
+    #[test]
+    fn normalize_leaves_non_commuting_moves() {
+        assert_eq!(notation("R U R' U'").normalize(), notation("R U R' U'"));
     }
 
     #[test]
     fn normalize_combines_single_moves() {
-        assert_eq!(notation!(C, C).normalize(), notation!(2 C));
-        assert_eq!(notation!(-B, -B).normalize(), notation!(2 B));
+        assert_eq!(notation("F F").normalize(), notation("F2"));
+        assert_eq!(notation("R' R'").normalize(), notation("R2"));
+        assert_eq!(notation("U2 U").normalize(), notation("U'"));
+        assert_eq!(notation("B3 B5").normalize(), notation(""));
     }
 
     #[test]
     fn normalize_cancels_moves() {
-        assert_eq!(notation!(A, -A).normalize(), notation!());
-        assert_eq!(notation!(-D, D).normalize(), notation!());
-        assert_eq!(notation!(2 B, 2 B).normalize(), notation!());
+        assert_eq!(notation("R R'").normalize(), notation(""));
+        assert_eq!(notation("D' D").normalize(), notation(""));
+        assert_eq!(notation("U2 U2").normalize(), notation(""));
+        assert_eq!(notation("R U U' R'").normalize(), notation(""));
     }
 
     #[test]
     fn normalize_combines_with_commutor_in_between() {
-        assert_eq!(notation!(B, A, B).normalize(), notation!(A, 2 B));
+        assert_eq!(notation("U D U").normalize(), notation("U2 D"));
+        assert_eq!(notation("R U D U' R'").normalize(), notation("R D R'"));
     }
 
-    */
-
-    /*
-    // TODO This isn't currently done
-
     #[test]
-    fn normalize_combines_with_cancel_in_between() {
-        assert_eq!(notation!(-B, C, -C, 2 B).normalize(), notation!(B));
+    fn normalize_preserves_permutation() {
+        let alg = notation("R U2 D' L L' U B2 F D3 U R2 R2 F'");
+        assert_eq!(alg.normalize().permutation(), alg.permutation());
+        assert_eq!(
+            alg.normalize(),
+            notation("R U' D' B2 F D' U F'").normalize()
+        );
     }
-    */
 }
-
-*/
@@ -1,166 +1,338 @@
 //! Generic puzzle notation parser.
 
+use std::convert::TryFrom;
 use std::str::FromStr;
 
 // TODO Use parsing framework? (even though this is very light-weight parsing, it is a bit verbose)
 
-use super::{NotationMove, NotationPrim, NotationStr};
+use super::{
+    NotationComment, NotationError, NotationErrorReason, NotationMove, NotationNode, NotationPrim,
+    NotationStr, NotationTree,
+};
 
-type ParseState<'a> = &'a str;
+type Result<T> = std::result::Result<T, NotationError>;
 
-// TODO Implement actual errors
-type ParseErr = ();
+/// Position in the input, kept so that errors can point at the offending token.
+struct ParseState<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> ParseState<'a> {
+    fn new(input: &'a str) -> Self {
+        ParseState { input, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.pos == self.input.len()
+    }
+
+    /// Consume leading characters matching a predicate and return them.
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn starts_comment(&self) -> bool {
+        self.rest().starts_with("//") || self.rest().starts_with("/*")
+    }
+
+    /// Length of the input from `offset` to the next whitespace, delimiter or comment.
+    fn word_len(&self, offset: usize) -> usize {
+        let word = &self.input[offset..];
+        word.find(|c: char| c.is_whitespace() || is_delimiter(c) || c == '/')
+            .unwrap_or(word.len())
+    }
 
-type Result<T> = std::result::Result<T, ParseErr>;
+    fn error(&self, offset: usize, len: usize, reason: NotationErrorReason) -> NotationError {
+        NotationError::new(self.input, offset, len, reason)
+    }
+}
 
-fn parse_prim<M: NotationPrim>(s: ParseState) -> Result<(M, ParseState)> {
-    // Read in leading alpha-numeric characters from input and compare against that
-    // Find first non-alpha character and split there
-    let alpha_len = s
-        .find(|c: char| !c.is_ascii_alphabetic())
-        .unwrap_or(s.len());
-    let (ident, s) = s.split_at(alpha_len);
+/// Characters which separate moves without needing whitespace.
+fn is_delimiter(c: char) -> bool {
+    matches!(c, '(' | ')' | '[' | ']' | ',' | ':')
+}
 
-    M::from_str(ident).map(|prim| (prim, s)).map_err(|_| ())
+fn parse_prim<M: NotationPrim>(s: &mut ParseState) -> Result<M> {
+    // Read in leading alphabetic characters from input and compare against that
+    let start = s.pos;
+    let ident = s.take_while(|c| c.is_ascii_alphabetic());
+
+    M::from_str(ident).map_err(|_| {
+        // Point at the whole word if there is no identifier at all (e.g. "2R")
+        let len = if ident.is_empty() {
+            s.word_len(start)
+        } else {
+            ident.len()
+        };
+        s.error(start, len, NotationErrorReason::UnknownPrim)
+    })
 }
 
-fn parse_num(s: ParseState) -> Result<(Option<u8>, ParseState)> {
-    let (n, len) = s
+fn parse_num(s: &mut ParseState) -> Result<Option<i8>> {
+    let start = s.pos;
+    let digits = s.take_while(|c| c.is_ascii_digit());
+    if digits.is_empty() {
+        return Ok(None);
+    }
+
+    // Digits will always be valid because of the take_while
+    let n = digits
         .chars()
-        .take_while(|c| c.is_digit(10))
-        .fold((0, 0), |(n, count), digit| {
-            // Return the number and how many digits were consumed
-            // Digit will always exist because of the take_while
-            let digit_val = digit.to_digit(10).unwrap();
-            (n * 10 + digit_val, count + 1)
-        });
-
-    if len == 0 {
-        Ok((None, s))
-    } else if n > 0 {
-        // TODO Check overflow
-        Ok((Some(n as u8), &s[len..]))
+        .try_fold(0u32, |n, digit| {
+            n.checked_mul(10)?.checked_add(digit.to_digit(10).unwrap())
+        })
+        .and_then(|n| i8::try_from(n).ok())
+        .ok_or_else(|| s.error(start, digits.len(), NotationErrorReason::CountOverflow))?;
+
+    if n > 0 {
+        Ok(Some(n))
     } else {
-        Err(())
+        Err(s.error(start, digits.len(), NotationErrorReason::ZeroCount))
     }
 }
 
-fn parse_prime(s: ParseState) -> Result<(bool, ParseState)> {
-    if s.chars().next() == Some('\'') {
-        Ok((true, &s[1..]))
+fn parse_prime(s: &mut ParseState) -> bool {
+    if s.rest().starts_with('\'') {
+        s.pos += 1;
+        true
     } else {
-        Ok((false, s))
+        false
     }
 }
 
-fn parse_count(s: ParseState) -> Result<(i8, ParseState)> {
-    let (opt_n, s) = parse_num(s)?;
-    // TODO Check overflow
-    let n = opt_n.unwrap_or(1) as i8;
+fn parse_count(s: &mut ParseState) -> Result<i8> {
+    let n = parse_num(s)?.unwrap_or(1);
+    let inverse = parse_prime(s);
+    Ok(if inverse { -n } else { n })
+}
 
-    let (inverse, s) = parse_prime(s)?;
+fn parse_move<M: NotationPrim>(s: &mut ParseState) -> Result<NotationMove<M>> {
+    let prim = parse_prim(s)?;
+    let count = parse_count(s)?;
 
-    let n = if inverse { -n } else { n };
+    Ok(NotationMove::ntimes(prim, count))
+}
 
-    Ok((n, s))
+/// Parses a `// line` or `/* block */` comment, with the start of the comment next in the input.
+fn parse_comment(s: &mut ParseState) -> Result<NotationComment> {
+    let start = s.pos;
+    let rest = s.rest();
+    if let Some(line) = rest.strip_prefix("//") {
+        let text = line.lines().next().unwrap_or("");
+        s.pos += 2 + text.len();
+        Ok(NotationComment::Line(text.trim().to_string()))
+    } else {
+        let block = &rest[2..];
+        let len = block
+            .find("*/")
+            .ok_or_else(|| s.error(start, 2, NotationErrorReason::UnclosedComment))?;
+        s.pos += 2 + len + 2;
+        Ok(NotationComment::Block(block[..len].trim().to_string()))
+    }
 }
 
-fn parse_move<M: NotationPrim>(s: ParseState) -> Result<(NotationMove<M>, ParseState)> {
-    let (prim, s) = parse_prim(s)?;
-    let (count, s) = parse_count(s)?;
+/// Fails if an item is followed by anything other than whitespace, a delimiter, a comment or the
+/// end of the input.
+fn expect_item_end(s: &ParseState) -> Result<()> {
+    match s.peek() {
+        Some(c) if !c.is_whitespace() && !is_delimiter(c) && !s.starts_comment() => {
+            Err(s.error(s.pos, s.word_len(s.pos), NotationErrorReason::TrailingInput))
+        }
+        _ => Ok(()),
+    }
+}
 
-    Ok((NotationMove::ntimes(prim, count), s))
+/// Consumes a closing bracket for the one at `open`.
+fn expect_close(s: &mut ParseState, open: usize, close: char) -> Result<()> {
+    match s.peek() {
+        Some(c) if c == close => {
+            s.pos += 1;
+            Ok(())
+        }
+        Some(c) => Err(s.error(
+            s.pos,
+            c.len_utf8(),
+            NotationErrorReason::UnexpectedDelimiter,
+        )),
+        None => Err(s.error(open, 1, NotationErrorReason::UnclosedBracket)),
+    }
 }
 
-fn parse_move_full<M: NotationPrim>(s: ParseState) -> Result<NotationMove<M>> {
-    let (m, s) = parse_move(s)?;
-    if s.is_empty() {
+/// Parses `(A)n`, with the opening parenthesis next in the input.
+fn parse_group<M: NotationPrim>(s: &mut ParseState) -> Result<NotationNode<M>> {
+    let open = s.pos;
+    s.pos += 1;
+    let nodes = parse_seq(s)?;
+    expect_close(s, open, ')')?;
+    let count = parse_count(s)?;
+
+    Ok(NotationNode::Group(nodes, count))
+}
+
+/// Parses `[A, B]` or `[A: B]`, with the opening bracket next in the input.
+fn parse_bracket<M: NotationPrim>(s: &mut ParseState) -> Result<NotationNode<M>> {
+    let open = s.pos;
+    s.pos += 1;
+    let a = parse_seq(s)?;
+
+    let is_commutator = match s.peek() {
+        Some(',') => true,
+        Some(':') => false,
+        Some(c) => {
+            return Err(s.error(
+                s.pos,
+                c.len_utf8(),
+                NotationErrorReason::UnexpectedDelimiter,
+            ))
+        }
+        None => return Err(s.error(open, 1, NotationErrorReason::UnclosedBracket)),
+    };
+    s.pos += 1;
+
+    let b = parse_seq(s)?;
+    expect_close(s, open, ']')?;
+
+    Ok(if is_commutator {
+        NotationNode::Commutator(a, b)
+    } else {
+        NotationNode::Conjugate(a, b)
+    })
+}
+
+/// Parses items until the end of the input or a closing delimiter, which is not consumed.
+///
+/// Comments are kept as items.
+fn parse_seq<M: NotationPrim>(s: &mut ParseState) -> Result<Vec<NotationNode<M>>> {
+    let mut nodes = Vec::new();
+
+    loop {
+        s.skip_whitespace();
+        let node = match s.peek() {
+            None | Some(')') | Some(']') | Some(',') | Some(':') => break,
+            Some('/') if s.starts_comment() => NotationNode::Comment(parse_comment(s)?),
+            Some('(') => parse_group(s)?,
+            Some('[') => parse_bracket(s)?,
+            Some(_) => NotationNode::Move(parse_move(s)?),
+        };
+
+        // Comments end themselves, so anything can come after them
+        if !matches!(node, NotationNode::Comment(_)) {
+            expect_item_end(s)?;
+        }
+        nodes.push(node);
+    }
+
+    Ok(nodes)
+}
+
+fn parse_move_full<M: NotationPrim>(s: &mut ParseState) -> Result<NotationMove<M>> {
+    let m = parse_move(s)?;
+    if s.is_at_end() {
         Ok(m)
     } else {
-        Err(())
+        Err(s.error(
+            s.pos,
+            s.input.len() - s.pos,
+            NotationErrorReason::TrailingInput,
+        ))
     }
 }
 
-/// Parses a string notation into move notation.
+/// Parses a string notation into structured notation.
 ///
 /// The notation string can be padded with whitespace.
-fn parse_notation<M: NotationPrim>(s: ParseState) -> Result<NotationStr<M>> {
-    s.split_whitespace()
-        .map(|move_str| parse_move_full(move_str))
-        .try_fold(Vec::new(), |moves, m| {
-            let mut moves = moves;
-            m.map(move |m| {
-                moves.push(m);
-                moves
-            })
-        })
-        .map(NotationStr::from)
+fn parse_tree<M: NotationPrim>(s: &mut ParseState) -> Result<NotationTree<M>> {
+    let nodes = parse_seq(s)?;
+
+    // Only stops early for a closing delimiter without an opening one
+    match s.peek() {
+        Some(c) => Err(s.error(
+            s.pos,
+            c.len_utf8(),
+            NotationErrorReason::UnexpectedDelimiter,
+        )),
+        None => Ok(NotationTree::from(nodes)),
+    }
 }
 
 impl<M: NotationPrim> FromStr for NotationMove<M> {
-    type Err = ParseErr;
+    type Err = NotationError;
 
     fn from_str(s: &str) -> Result<Self> {
-        parse_move_full(s)
+        parse_move_full(&mut ParseState::new(s))
     }
 }
 
 impl<M: NotationPrim> FromStr for NotationStr<M> {
-    type Err = ParseErr;
+    type Err = NotationError;
 
+    /// Parses structured notation and expands it.
     fn from_str(s: &str) -> Result<Self> {
-        parse_notation(s)
+        parse_tree(&mut ParseState::new(s)).map(|tree: NotationTree<M>| tree.expand())
+    }
+}
+
+impl<M: NotationPrim> FromStr for NotationTree<M> {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_tree(&mut ParseState::new(s))
     }
 }
 
-/*
 #[cfg(test)]
 mod test {
     use super::*;
 
-    use super::super::test::TestPrim;
+    use crate::cube::cube3::notation::Cube3Notation::{self, *};
 
     #[test]
     fn parses_basic_move() {
-        assert_eq!(
-            NotationMove::from_str("A"),
-            Ok(NotationMove::basic(TestPrim::A))
-        );
+        assert_eq!(NotationMove::from_str("U"), Ok(NotationMove::basic(U)));
     }
 
     #[test]
     fn parses_inverse_move() {
-        assert_eq!(
-            NotationMove::from_str("B'"),
-            Ok(NotationMove::inverse(TestPrim::B))
-        );
+        assert_eq!(NotationMove::from_str("R'"), Ok(NotationMove::inverse(R)));
     }
 
     #[test]
     fn parses_ntimes_move() {
-        assert_eq!(
-            NotationMove::from_str("C3"),
-            Ok(NotationMove::ntimes(TestPrim::C, 3))
-        );
+        assert_eq!(NotationMove::from_str("F3"), Ok(NotationMove::ntimes(F, 3)));
     }
 
     #[test]
     fn parses_ntimes_inverse_move() {
         assert_eq!(
             NotationMove::from_str("D3'"),
-            Ok(NotationMove::ntimes(TestPrim::D, -3))
+            Ok(NotationMove::ntimes(D, -3))
         );
     }
 
     #[test]
     fn parses_notation_str() {
-        use TestPrim::*;
         assert_eq!(
-            NotationStr::from_str(" A\tB' C3   D2'  "),
+            NotationStr::from_str(" U\tR' F3   D2'  "),
             Ok(NotationStr::from(vec![
-                NotationMove::basic(A),
-                NotationMove::inverse(B),
-                NotationMove::ntimes(C, 3),
+                NotationMove::basic(U),
+                NotationMove::inverse(R),
+                NotationMove::ntimes(F, 3),
                 NotationMove::ntimes(D, -2)
             ]))
         );
@@ -168,24 +340,177 @@ mod test {
 
     // Test errors
 
+    fn parse_error(s: &str) -> (usize, String, NotationErrorReason) {
+        let err = NotationStr::<Cube3Notation>::from_str(s).unwrap_err();
+        (err.offset(), err.token().to_string(), err.reason())
+    }
+
     #[test]
     fn fails_on_invalid_move() {
-        assert_eq!(NotationMove::<TestPrim>::from_str("Q"), Err(()));
+        assert_eq!(
+            NotationMove::<Cube3Notation>::from_str("Q").map_err(|err| err.reason()),
+            Err(NotationErrorReason::UnknownPrim)
+        );
+        assert_eq!(
+            parse_error("U 2R"),
+            (2, "2R".to_string(), NotationErrorReason::UnknownPrim)
+        );
     }
 
     #[test]
     fn fails_on_zero_count() {
-        assert_eq!(NotationMove::<TestPrim>::from_str("A0"), Err(()));
+        assert_eq!(
+            parse_error("U R0 F"),
+            (3, "0".to_string(), NotationErrorReason::ZeroCount)
+        );
+    }
+
+    #[test]
+    fn fails_on_count_overflow() {
+        assert_eq!(
+            parse_error("U128"),
+            (1, "128".to_string(), NotationErrorReason::CountOverflow)
+        );
+        assert_eq!(
+            parse_error("U99999999999"),
+            (
+                1,
+                "99999999999".to_string(),
+                NotationErrorReason::CountOverflow
+            )
+        );
+        assert_eq!(
+            NotationMove::from_str("U127'"),
+            Ok(NotationMove::ntimes(U, -127))
+        );
     }
 
     #[test]
     fn fails_on_text_after_move() {
-        assert_eq!(NotationMove::<TestPrim>::from_str("A2A"), Err(()));
+        assert_eq!(
+            NotationMove::<Cube3Notation>::from_str("R2R").map_err(|err| err.reason()),
+            Err(NotationErrorReason::TrailingInput)
+        );
+        assert_eq!(
+            parse_error("U R2'x F"),
+            (5, "x".to_string(), NotationErrorReason::TrailingInput)
+        );
     }
 
     #[test]
     fn fails_on_notation_str_with_invalid_move() {
-        assert_eq!(NotationStr::<TestPrim>::from_str("A B' Q3 D2'"), Err(()));
+        assert_eq!(
+            parse_error("U R' Q3 D2'"),
+            (5, "Q".to_string(), NotationErrorReason::UnknownPrim)
+        );
+    }
+
+    #[test]
+    fn parses_groups_and_brackets() {
+        use NotationNode::*;
+        assert_eq!(
+            NotationTree::from_str("(R U')2 [R,U] [F: [R, U]]"),
+            Ok(NotationTree::from(vec![
+                Group(
+                    vec![Move(NotationMove::basic(R)), Move(NotationMove::inverse(U))],
+                    2
+                ),
+                Commutator(
+                    vec![Move(NotationMove::basic(R))],
+                    vec![Move(NotationMove::basic(U))]
+                ),
+                Conjugate(
+                    vec![Move(NotationMove::basic(F))],
+                    vec![Commutator(
+                        vec![Move(NotationMove::basic(R))],
+                        vec![Move(NotationMove::basic(U))]
+                    )]
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn fails_on_unclosed_bracket() {
+        assert_eq!(
+            parse_error("U (R U"),
+            (2, "(".to_string(), NotationErrorReason::UnclosedBracket)
+        );
+        assert_eq!(
+            parse_error("[R, [U: F]"),
+            (0, "[".to_string(), NotationErrorReason::UnclosedBracket)
+        );
+    }
+
+    #[test]
+    fn fails_on_unexpected_delimiter() {
+        assert_eq!(
+            parse_error("R U) F"),
+            (3, ")".to_string(), NotationErrorReason::UnexpectedDelimiter)
+        );
+        assert_eq!(
+            parse_error("[R U]"),
+            (4, "]".to_string(), NotationErrorReason::UnexpectedDelimiter)
+        );
+        assert_eq!(
+            parse_error("(R, U)"),
+            (2, ",".to_string(), NotationErrorReason::UnexpectedDelimiter)
+        );
+    }
+
+    #[test]
+    fn fails_on_zero_group_count() {
+        assert_eq!(
+            parse_error("(R U)0"),
+            (5, "0".to_string(), NotationErrorReason::ZeroCount)
+        );
+    }
+
+    #[test]
+    fn parses_comments() {
+        use NotationNode::*;
+        assert_eq!(
+            NotationTree::from_str("z2 // inspection\nR /* pair */ U'//cross\n[R,/*x*/U]"),
+            Ok(NotationTree::from(vec![
+                Move(NotationMove::ntimes(Z, 2)),
+                Comment(NotationComment::Line("inspection".to_string())),
+                Move(NotationMove::basic(R)),
+                Comment(NotationComment::Block("pair".to_string())),
+                Move(NotationMove::inverse(U)),
+                Comment(NotationComment::Line("cross".to_string())),
+                Commutator(
+                    vec![Move(NotationMove::basic(R))],
+                    vec![
+                        Comment(NotationComment::Block("x".to_string())),
+                        Move(NotationMove::basic(U))
+                    ]
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn notation_str_skips_comments() {
+        assert_eq!(
+            NotationStr::<Cube3Notation>::from_str("R U /* R' */ // U'\nF // end"),
+            NotationStr::from_str("R U F")
+        );
+    }
+
+    #[test]
+    fn fails_on_unclosed_comment() {
+        assert_eq!(
+            parse_error("R U /* R'"),
+            (4, "/*".to_string(), NotationErrorReason::UnclosedComment)
+        );
+    }
+
+    #[test]
+    fn error_points_at_token() {
+        let err = NotationStr::<Cube3Notation>::from_str("U R' Xy3 D2'\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown move \"Xy\" at offset 5\nU R' Xy3 D2'\n     ^^"
+        );
     }
 }
-*/
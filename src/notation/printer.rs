@@ -1,35 +1,133 @@
 use std::fmt::{self, Write};
 
-use super::{NotationMove, NotationPrim, NotationStr};
+use super::{
+    NotationComment, NotationMove, NotationNode, NotationPrim, NotationStep, NotationStr,
+    NotationTree,
+};
+
+/// Writes the suffix for a move or group count.
+fn write_count(f: &mut fmt::Formatter, count: i8) -> fmt::Result {
+    // Only show number if not 1
+    let abs = count.abs();
+    if abs != 1 {
+        write!(f, "{}", abs)?;
+    }
+
+    // Show ' for inverses
+    if count.is_negative() {
+        f.write_char('\'')?;
+    }
+
+    Ok(())
+}
+
+/// Writes items separated by spaces.
+fn write_spaced<T: fmt::Display>(f: &mut fmt::Formatter, items: &[T]) -> fmt::Result {
+    if !items.is_empty() {
+        // Print first separately so spaces can be put in between
+        write!(f, "{}", items[0])?;
+
+        for item in &items[1..] {
+            write!(f, " {}", item)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes notation nodes separated by spaces, or new lines after line comments.
+///
+/// Nested sequences also end with a new line after a line comment, so that it does not swallow
+/// the closing bracket.
+fn write_nodes<M: NotationPrim>(
+    f: &mut fmt::Formatter,
+    nodes: &[NotationNode<M>],
+    nested: bool,
+) -> fmt::Result {
+    let mut after_line_comment = false;
+    for (index, node) in nodes.iter().enumerate() {
+        if after_line_comment {
+            f.write_char('\n')?;
+        } else if index > 0 {
+            f.write_char(' ')?;
+        }
+
+        write!(f, "{}", node)?;
+        after_line_comment = matches!(node, NotationNode::Comment(NotationComment::Line(_)));
+    }
+
+    if nested && after_line_comment {
+        f.write_char('\n')?;
+    }
+
+    Ok(())
+}
 
 impl<M: NotationPrim> fmt::Display for NotationMove<M> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&self.prim.to_string())?;
+        write_count(f, self.count)
+    }
+}
 
-        // Only show number if not 1
-        let abs = self.count.abs();
-        if abs != 1 {
-            write!(f, "{}", abs)?;
-        }
+impl<M: NotationPrim> fmt::Display for NotationStr<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_spaced(f, &self.moves)
+    }
+}
 
-        // Show ' for inverses
-        if self.count.is_negative() {
-            f.write_char('\'')?;
+impl<M: NotationPrim> fmt::Display for NotationNode<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotationNode::Move(m) => write!(f, "{}", m),
+            NotationNode::Group(nodes, count) => {
+                f.write_char('(')?;
+                write_nodes(f, nodes, true)?;
+                f.write_char(')')?;
+                write_count(f, *count)
+            }
+            NotationNode::Commutator(a, b) => {
+                f.write_char('[')?;
+                write_nodes(f, a, true)?;
+                f.write_str(", ")?;
+                write_nodes(f, b, true)?;
+                f.write_char(']')
+            }
+            NotationNode::Conjugate(a, b) => {
+                f.write_char('[')?;
+                write_nodes(f, a, true)?;
+                f.write_str(": ")?;
+                write_nodes(f, b, true)?;
+                f.write_char(']')
+            }
+            NotationNode::Comment(comment) => write!(f, "{}", comment),
         }
+    }
+}
 
-        Ok(())
+impl fmt::Display for NotationComment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotationComment::Line(text) => write!(f, "// {}", text),
+            NotationComment::Block(text) => write!(f, "/* {} */", text),
+        }
     }
 }
 
-impl<M: NotationPrim> fmt::Display for NotationStr<M> {
+impl<M: NotationPrim> fmt::Display for NotationTree<M> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if !self.is_empty() {
-            // Print first separately so spaces can be put in between
-            write!(f, "{}", self.moves[0])?;
+        write_nodes(f, self.nodes(), false)
+    }
+}
 
-            for m in &self.moves[1..] {
-                write!(f, " {}", m)?;
+impl<M: NotationPrim> fmt::Display for NotationStep<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.moves)?;
+        if let Some(label) = &self.label {
+            if !self.moves.nodes().is_empty() {
+                f.write_char(' ')?;
             }
+            write!(f, "{}", NotationComment::Line(label.clone()))?;
         }
 
         Ok(())
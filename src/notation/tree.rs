@@ -0,0 +1,269 @@
+//! Structured notation with groups, commutators, conjugates and comments.
+
+use super::{NotationMove, NotationPrim, NotationStr};
+
+/// Comment in a notation string.
+///
+/// The text is stored without the comment markers or surrounding whitespace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotationComment {
+    /// `// text`, which runs to the end of the line.
+    ///
+    /// At the top level, these label the moves before them as a step.
+    Line(String),
+
+    /// `/* text */`
+    Block(String),
+}
+
+/// Element of a structured notation sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotationNode<M: NotationPrim> {
+    Move(NotationMove<M>),
+
+    /// Sequence in parentheses, repeated a number of times (e.g. `(R U R' U')3`).
+    ///
+    /// A negative count repeats the inverse of the sequence.
+    Group(Vec<NotationNode<M>>, i8),
+
+    /// `[A, B]`, which expands to `A B A' B'`.
+    Commutator(Vec<NotationNode<M>>, Vec<NotationNode<M>>),
+
+    /// `[A: B]`, which expands to `A B A'`.
+    Conjugate(Vec<NotationNode<M>>, Vec<NotationNode<M>>),
+
+    /// Comment, which has no effect on the moves.
+    Comment(NotationComment),
+}
+
+impl<M: NotationPrim> NotationNode<M> {
+    fn expand_into(&self, moves: &mut Vec<NotationMove<M>>) {
+        match self {
+            NotationNode::Move(m) => moves.push(*m),
+            NotationNode::Group(nodes, count) => {
+                let mut group = expand_nodes(nodes);
+                if *count < 0 {
+                    group = group.invert();
+                }
+                for _ in 0..count.unsigned_abs() {
+                    moves.extend_from_slice(&group.moves);
+                }
+            }
+            NotationNode::Commutator(a, b) => {
+                let (a, b) = (expand_nodes(a), expand_nodes(b));
+                moves.extend_from_slice(&a.moves);
+                moves.extend_from_slice(&b.moves);
+                moves.extend(a.invert().moves);
+                moves.extend(b.invert().moves);
+            }
+            NotationNode::Conjugate(a, b) => {
+                let a = expand_nodes(a);
+                moves.extend_from_slice(&a.moves);
+                expand_nodes_into(b, moves);
+                moves.extend(a.invert().moves);
+            }
+            NotationNode::Comment(_) => {}
+        }
+    }
+}
+
+fn expand_nodes_into<M: NotationPrim>(nodes: &[NotationNode<M>], moves: &mut Vec<NotationMove<M>>) {
+    for node in nodes {
+        node.expand_into(moves);
+    }
+}
+
+fn expand_nodes<M: NotationPrim>(nodes: &[NotationNode<M>]) -> NotationStr<M> {
+    let mut moves = Vec::new();
+    expand_nodes_into(nodes, &mut moves);
+    NotationStr { moves }
+}
+
+/// Part of a notation sequence, labeled by the line comment after it.
+///
+/// This is used to split up reconstructions, where each line is usually a step of the solve (e.g.
+/// `D' R' D2 F // cross`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotationStep<M: NotationPrim> {
+    pub moves: NotationTree<M>,
+    pub label: Option<String>,
+}
+
+/// Notation sequence which keeps the structure it was written with.
+///
+/// This is parsed from and printed in the compact form, and can be expanded into a flat
+/// `NotationStr` to apply it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotationTree<M: NotationPrim> {
+    nodes: Vec<NotationNode<M>>,
+}
+
+impl<M: NotationPrim> Default for NotationTree<M> {
+    fn default() -> Self {
+        NotationTree { nodes: vec![] }
+    }
+}
+
+impl<M: NotationPrim> From<Vec<NotationNode<M>>> for NotationTree<M> {
+    fn from(nodes: Vec<NotationNode<M>>) -> Self {
+        NotationTree { nodes }
+    }
+}
+
+impl<M: NotationPrim> NotationTree<M> {
+    pub fn nodes(&self) -> &[NotationNode<M>] {
+        &self.nodes
+    }
+
+    /// Expand groups, commutators and conjugates into a flat sequence of moves.
+    ///
+    /// Comments are dropped.
+    pub fn expand(&self) -> NotationStr<M> {
+        expand_nodes(&self.nodes)
+    }
+
+    /// Split the sequence into steps at each top-level line comment, which labels the step before
+    /// it.
+    ///
+    /// Anything after the last line comment is an unlabeled step. Block comments are kept in the
+    /// steps they are in.
+    pub fn steps(&self) -> Vec<NotationStep<M>> {
+        let mut steps = Vec::new();
+        let mut nodes = Vec::new();
+
+        for node in &self.nodes {
+            match node {
+                NotationNode::Comment(NotationComment::Line(label)) => {
+                    steps.push(NotationStep {
+                        moves: NotationTree::from(std::mem::take(&mut nodes)),
+                        label: Some(label.clone()),
+                    });
+                }
+                node => nodes.push(node.clone()),
+            }
+        }
+
+        if !nodes.is_empty() {
+            steps.push(NotationStep {
+                moves: NotationTree::from(nodes),
+                label: None,
+            });
+        }
+
+        steps
+    }
+}
+
+/// Joins steps back together, with their labels as line comments.
+impl<M: NotationPrim> From<Vec<NotationStep<M>>> for NotationTree<M> {
+    fn from(steps: Vec<NotationStep<M>>) -> Self {
+        let mut nodes = Vec::new();
+        for step in steps {
+            nodes.extend(step.moves.nodes);
+            if let Some(label) = step.label {
+                nodes.push(NotationNode::Comment(NotationComment::Line(label)));
+            }
+        }
+
+        NotationTree { nodes }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::cube::cube3::notation::Cube3Notation;
+
+    fn tree(s: &str) -> NotationTree<Cube3Notation> {
+        s.parse().unwrap()
+    }
+
+    fn flat(s: &str) -> NotationStr<Cube3Notation> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn expands_repeated_group() {
+        assert_eq!(
+            tree("(R U R' U')3").expand(),
+            flat("R U R' U' R U R' U' R U R' U'")
+        );
+        assert_eq!(tree("F (R U)2' B").expand(), flat("F U' R' U' R' B"));
+    }
+
+    #[test]
+    fn expands_commutator() {
+        assert_eq!(tree("[R, U]").expand(), flat("R U R' U'"));
+        assert_eq!(tree("[R U, D2]").expand(), flat("R U D2 U' R' D2'"));
+    }
+
+    #[test]
+    fn expands_conjugate() {
+        assert_eq!(tree("[F: R U R' U']").expand(), flat("F R U R' U' F'"));
+    }
+
+    #[test]
+    fn expands_nested_brackets() {
+        assert_eq!(
+            tree("[F: [R, U]] ([U': R2])2").expand(),
+            flat("F R U R' U' F' U' R2 U U' R2 U")
+        );
+    }
+
+    #[test]
+    fn prints_compact_form() {
+        for s in [
+            "(R U R' U')3",
+            "[F: [R, U]]",
+            "[R U R', D2] (M' U)4'",
+            "x2 (R2 U)2 [Rw U Rw': U2]",
+        ] {
+            assert_eq!(tree(s).to_string(), s);
+        }
+
+        assert_eq!(tree(" [ R ,U]( F )").to_string(), "[R, U] (F)");
+    }
+
+    const RECONSTRUCTION: &str = "\
+z2 // inspection
+D' R' /* x-cross */ D2 F // cross
+[U: R U' R'] // pair
+(R U R' U')2 y";
+
+    #[test]
+    fn expand_skips_comments() {
+        assert_eq!(
+            tree(RECONSTRUCTION).expand(),
+            flat("z2 D' R' D2 F U R U' R' U' R U R' U' R U R' U' y")
+        );
+    }
+
+    #[test]
+    fn prints_comments() {
+        assert_eq!(tree(RECONSTRUCTION).to_string(), RECONSTRUCTION);
+        assert_eq!(
+            tree("[R // setup\n, U /* b */]").to_string(),
+            "[R // setup\n, U /* b */]"
+        );
+    }
+
+    #[test]
+    fn splits_labeled_steps() {
+        let steps = tree(RECONSTRUCTION).steps();
+        let labels = steps
+            .iter()
+            .map(|step| step.label.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec![Some("inspection"), Some("cross"), Some("pair"), None]
+        );
+
+        assert_eq!(steps[1].moves, tree("D' R' /* x-cross */ D2 F"));
+        assert_eq!(steps[1].to_string(), "D' R' /* x-cross */ D2 F // cross");
+        assert_eq!(steps[3].moves, tree("(R U R' U')2 y"));
+
+        assert_eq!(NotationTree::from(steps), tree(RECONSTRUCTION));
+    }
+}
@@ -0,0 +1,778 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use num_traits::FromPrimitive;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+use crate::coord::{CompositeCoord, Coord};
+use crate::move_table::MoveTable;
+use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::symmetry::{RepresentantTable, SymConjTable, SymCoord};
+use crate::table_io::{self, Header, LoadError, MappedTable, TableKind};
+use crate::util::{self, EnumCount, EnumIndex, IntoEnumIterator};
+
+mod packed;
+
+pub use packed::{Mod3PruneTable, PackedPruneTable};
+
+/// Bound returned for states which can't reach the target.
+///
+/// Tables with exact distances return at least this for entries which were never filled, so
+/// searches can tell them apart from states which are far away.
+pub const UNREACHABLE: u32 = u8::MAX as u32;
+
+pub trait PruneTable {
+    /// Puzzle this pruning table is for.
+    type Puzzle: PuzzlePerm;
+
+    /// Coordinate or composite coordinate this pruning table is indexed by.
+    type Coord: Coord<Self::Puzzle>;
+
+    /// Move set used to count minimum bounds.
+    type Move: PuzzleMove<Puzzle = Self::Puzzle>;
+
+    // TODO Make u8? Will it every be larger than that?
+    /// Returns a lower bound for the number of moves it will take to get to a particular state.
+    fn get_min_moves(&self, coord: Self::Coord) -> u32;
+
+    /// Returns a lower bound for the number of moves to a state, given the bound for the state one
+    /// move before it.
+    ///
+    /// This is the same as `get_min_moves` unless the table needs the extra context, like
+    /// `Mod3PruneTable`. In that case, `parent_moves` must be exact, so it can't be used when the
+    /// table is wrapped in something like `CompositePruneTable`.
+    fn get_min_moves_from(&self, coord: Self::Coord, parent_moves: u32) -> u32 {
+        let _ = parent_moves;
+        self.get_min_moves(coord)
+    }
+}
+
+/// Pruning table which always returns 0
+#[derive(Clone, Debug)]
+pub struct ZeroPruneTable<C, M>(PhantomData<(C, M)>);
+
+impl<C, M> Default for ZeroPruneTable<C, M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<P, C, M> PruneTable for ZeroPruneTable<C, M>
+where
+    P: PuzzlePerm,
+    C: Coord<P>,
+    M: PuzzleMove<Puzzle = P>,
+{
+    type Puzzle = P;
+    type Coord = C;
+    type Move = M;
+
+    fn get_min_moves(&self, _coord: Self::Coord) -> u32 {
+        0
+    }
+}
+
+/// Encoding of the entries of a pruning table.
+///
+/// Entries can take up less than a byte, in which case multiple are packed into each byte, starting
+/// from the least significant bits.
+pub(crate) trait Encoding {
+    /// Number of bits in each entry. Must be a divisor of 8.
+    const BITS: usize;
+
+    /// Value of entries which have not been filled.
+    const UNFILLED: u8 = u8::MAX >> (8 - Self::BITS);
+
+    /// Greatest distance which can be stored. Entries which are further away are left unfilled.
+    const MAX_DIST: u8 = Self::UNFILLED - 1;
+
+    /// Returns the value stored for a distance.
+    fn encode(dist: u8) -> u8 {
+        dist
+    }
+}
+
+/// One entry per byte, storing the exact distance.
+pub(crate) struct Bytes;
+
+impl Encoding for Bytes {
+    const BITS: usize = 8;
+}
+
+/// Returns the number of bytes needed to store `count` entries.
+pub(crate) fn table_len<E: Encoding>(count: usize) -> usize {
+    count.div_ceil(8 / E::BITS)
+}
+
+/// Returns the byte containing an entry and the shift of the entry within it.
+fn entry_position<E: Encoding>(index: usize) -> (usize, usize) {
+    let per_byte = 8 / E::BITS;
+    (index / per_byte, index % per_byte * E::BITS)
+}
+
+/// Get an entry from a table.
+pub(crate) fn get_entry<E: Encoding>(table: &[u8], index: usize) -> u8 {
+    let (byte, shift) = entry_position::<E>(index);
+    (table[byte] >> shift) & E::UNFILLED
+}
+
+fn load_entry<E: Encoding>(table: &[AtomicU8], index: usize) -> u8 {
+    let (byte, shift) = entry_position::<E>(index);
+    (table[byte].load(Ordering::Relaxed) >> shift) & E::UNFILLED
+}
+
+/// Fill in an entry if it is not already filled.
+///
+/// Returns whether the entry was filled.
+fn fill_entry<E: Encoding>(table: &[AtomicU8], index: usize, value: u8) -> bool {
+    let (byte, shift) = entry_position::<E>(index);
+    table[byte]
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+            if (bits >> shift) & E::UNFILLED == E::UNFILLED {
+                Some((bits & !(E::UNFILLED << shift)) | (value << shift))
+            } else {
+                None
+            }
+        })
+        .is_ok()
+}
+
+/// Fill a pruning table with the distance of each coordinate from the closest target.
+///
+/// All entries must start out unfilled.
+pub(crate) fn fill_table<E, MT>(table: &mut [u8], move_table: &MT, targets: &[MT::Coord])
+where
+    E: Encoding,
+    MT: MoveTable,
+{
+    let targets = targets.iter().map(|target| target.index());
+    fill_table_with::<E, _>(table, MT::Coord::COUNT, targets, |table, forward, n| {
+        search_layer::<E, _, _>(table, move_table, forward, n, MT::Coord::iter())
+    });
+}
+
+/// Fill a pruning table like `fill_table`, splitting the work between threads.
+///
+/// Each layer of the search is split into chunks which are searched in parallel. Entries are only
+/// ever filled with their distance, so the result is the same as `fill_table`.
+///
+/// The work is done in `pool` if given, or in the global thread pool otherwise.
+pub(crate) fn fill_table_parallel<E, MT>(
+    table: &mut [u8],
+    move_table: &MT,
+    targets: &[MT::Coord],
+    pool: Option<&ThreadPool>,
+) where
+    E: Encoding,
+    MT: MoveTable + Sync,
+    MT::Coord: FromPrimitive,
+{
+    const CHUNK_SIZE: usize = 1 << 16;
+
+    let targets = targets
+        .iter()
+        .map(|target| target.index())
+        .collect::<Vec<_>>();
+    util::install(pool, || {
+        let targets = targets.into_iter();
+        fill_table_with::<E, _>(table, MT::Coord::COUNT, targets, |table, forward, n| {
+            (0..MT::Coord::COUNT.div_ceil(CHUNK_SIZE))
+                .into_par_iter()
+                .map(|chunk| {
+                    let start = chunk * CHUNK_SIZE;
+                    let end = std::cmp::min(start + CHUNK_SIZE, MT::Coord::COUNT);
+                    let coords = (start..end).map(|i| MT::Coord::from_usize(i).unwrap());
+                    search_layer::<E, _, _>(table, move_table, forward, n, coords)
+                })
+                .sum()
+        })
+    });
+}
+
+/// Runs the search which fills a pruning table of `count` entries, using `search_layer` to search
+/// each layer.
+///
+/// `search_layer` is given the table, whether to do a forward search, and the distance of the
+/// current layer, and returns the number of entries it filled.
+fn fill_table_with<E, F>(
+    table: &mut [u8],
+    count: usize,
+    targets: impl Iterator<Item = usize>,
+    search_layer: F,
+) where
+    E: Encoding,
+    F: Fn(&[AtomicU8], bool, u8) -> usize,
+{
+    // Safety: `AtomicU8` has the same size and alignment as `u8`, and the table is borrowed
+    // mutably, so nothing else can access it while it is being filled.
+    let table = unsafe { &*(table as *mut [u8] as *const [AtomicU8]) };
+
+    // Fill in the targets
+    let filled = targets
+        .filter(|&target| fill_entry::<E>(table, target, E::encode(0)))
+        .count();
+
+    let mut remaining = count - filled;
+    let mut n = 0;
+
+    // The table is filled with 2 methods:
+    // - Look for elements with a distance of n, apply all moves to those, and fill in the
+    //   resulting coordinates with n + 1 if they have not already been filled. ("forward
+    //   search")
+    // - Look for unfilled elements, apply all moves to them, and fill in the unfilled element
+    //   with n + 1 if one of the moves goes to an element with distance n. ("reverse search")
+    //
+    // The critical path in both algorithms is applying each move to a coordinate. In the
+    // forward search, this is done for entries with a value of n, while in the reverse search
+    // it is done for unfilled entries. For this reason, the reverse search is faster when the
+    // number of remaining entries is less than the number that were filled in the last
+    // iteration.
+    //
+    // Entries only store `E::encode` of their distance, which may be the same for multiple
+    // distances. This is fine for the forward search since entries which are closer than n have
+    // already had all of their neighbors filled.
+
+    // Forward search
+    while n < E::MAX_DIST {
+        info!("Forward search at distance {}", n);
+
+        // Keep track of the number filled to know when to start reverse search
+        let filled = search_layer(table, true, n);
+
+        n += 1;
+        info!("{} filled at distance {}", filled, n);
+        remaining -= filled;
+
+        // If there are fewer left than were filled in this iteration, the "reverse search" will
+        // be more efficient
+        // (also exits if the rest can't be reached)
+        if remaining <= filled || filled == 0 {
+            break;
+        }
+    }
+
+    // Reverse search
+    while remaining > 0 && n < E::MAX_DIST {
+        info!("Reverse search at distance {}", n);
+
+        // Keep track of this for logging purposes
+        let filled = search_layer(table, false, n);
+
+        n += 1;
+        info!("{} filled at distance {}", filled, n);
+        remaining -= filled;
+
+        if filled == 0 {
+            break;
+        }
+    }
+}
+
+/// Search one layer of the table, only looking at `coords`.
+///
+/// Returns the number of entries filled.
+fn search_layer<E, MT, I>(
+    table: &[AtomicU8],
+    move_table: &MT,
+    forward: bool,
+    n: u8,
+    coords: I,
+) -> usize
+where
+    E: Encoding,
+    MT: MoveTable,
+    I: Iterator<Item = MT::Coord>,
+{
+    let mut filled = 0;
+
+    if forward {
+        // Look for indices with distance n and apply all moves to them.
+        for coord in coords {
+            if load_entry::<E>(table, coord.index()) == E::encode(n) {
+                // Apply each move
+                for m in MT::Move::iter() {
+                    let new_index = move_table.get_move(coord, m).index();
+
+                    // Fill in the table if not already filled
+                    if load_entry::<E>(table, new_index) == E::UNFILLED
+                        && fill_entry::<E>(table, new_index, E::encode(n + 1))
+                    {
+                        filled += 1;
+                    }
+                }
+            }
+        }
+    } else {
+        for coord in coords {
+            let index = coord.index();
+            if load_entry::<E>(table, index) == E::UNFILLED {
+                // Fill in the table if one of the moves goes to a coordinate at distance n
+                let found = MT::Move::iter().any(|m| {
+                    load_entry::<E>(table, move_table.get_move(coord, m).index()) == E::encode(n)
+                });
+                if found && fill_entry::<E>(table, index, E::encode(n + 1)) {
+                    filled += 1;
+                }
+            }
+        }
+    }
+
+    filled
+}
+
+/// An exact-valued pruning table calculated from a coordinate move table.
+///
+/// This can be used with any coordinate, including composite coordinates, that has a move table.
+/// Note that for a composite coordinate with sub-coordinate sizes `A` and `B`, this pruning table
+/// will have size `A*B`. If that is not desired, pruning tables should be made individually for
+/// each of the sub-coordinates and combined with `CompositePruneTable`.
+///
+/// The entries are usually owned, but they can also be memory-mapped from a file (see
+/// `FullPruneTable::map`).
+pub struct FullPruneTable<C, M, D = Box<[u8]>> {
+    table: D,
+    _phantoms: PhantomData<(C, M)>,
+}
+
+impl<P, C, M, D> PruneTable for FullPruneTable<C, M, D>
+where
+    P: PuzzlePerm,
+    C: Coord<P>,
+    M: PuzzleMove<Puzzle = P>,
+    D: AsRef<[u8]>,
+{
+    type Puzzle = P;
+    type Coord = C;
+    type Move = M;
+
+    fn get_min_moves(&self, coord: C) -> u32 {
+        self.table.as_ref()[coord.index()] as u32
+    }
+}
+
+impl<P, C, M> FullPruneTable<C, M>
+where
+    P: PuzzlePerm,
+    C: Coord<P>,
+    M: PuzzleMove<Puzzle = P>,
+{
+    /// Create a pruning table from a move table.
+    ///
+    /// The resulting pruning table will have the same number of values as the coordinate `C`.
+    pub fn create<MT>(move_table: &MT, target: C) -> Self
+    where
+        MT: MoveTable<Puzzle = P, Coord = C, Move = M>,
+    {
+        Self::create_with_targets(move_table, &[target])
+    }
+
+    /// Create a pruning table giving the distance to the closest of a set of targets.
+    ///
+    /// This is useful for goals which can be reached in multiple ways, like having a cross solved
+    /// on any face.
+    pub fn create_with_targets<MT>(move_table: &MT, targets: &[C]) -> Self
+    where
+        MT: MoveTable<Puzzle = P, Coord = C, Move = M>,
+    {
+        info!("Building pruning table");
+
+        // Initialize with max to indicate unfilled
+        let mut table = vec![u8::MAX; C::COUNT].into_boxed_slice();
+        fill_table::<Bytes, _>(&mut table, move_table, targets);
+
+        Self {
+            table,
+            _phantoms: PhantomData,
+        }
+    }
+
+    /// Create a pruning table like `create`, splitting the work between threads.
+    ///
+    /// The work is done in `pool` if given, or in the global thread pool otherwise. The resulting
+    /// table is identical to the one from `create`.
+    pub fn create_parallel<MT>(move_table: &MT, target: C, pool: Option<&ThreadPool>) -> Self
+    where
+        MT: MoveTable<Puzzle = P, Coord = C, Move = M> + Sync,
+        C: FromPrimitive,
+    {
+        Self::create_parallel_with_targets(move_table, &[target], pool)
+    }
+
+    /// Create a pruning table like `create_with_targets`, splitting the work between threads.
+    pub fn create_parallel_with_targets<MT>(
+        move_table: &MT,
+        targets: &[C],
+        pool: Option<&ThreadPool>,
+    ) -> Self
+    where
+        MT: MoveTable<Puzzle = P, Coord = C, Move = M> + Sync,
+        C: FromPrimitive,
+    {
+        info!("Building pruning table in parallel");
+
+        let mut table = vec![u8::MAX; C::COUNT].into_boxed_slice();
+        fill_table_parallel::<Bytes, _>(&mut table, move_table, targets, pool);
+
+        Self {
+            table,
+            _phantoms: PhantomData,
+        }
+    }
+
+    /// Read a table written by `save`.
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, LoadError> {
+        let header = Header::new::<C, M>(TableKind::Prune, C::COUNT);
+        let table = table_io::read_table(&mut reader, &header, 1)?;
+
+        Ok(Self {
+            table: table.into_boxed_slice(),
+            _phantoms: PhantomData,
+        })
+    }
+}
+
+impl<P, C, M> FullPruneTable<C, M, MappedTable>
+where
+    P: PuzzlePerm,
+    C: Coord<P>,
+    M: PuzzleMove<Puzzle = P>,
+{
+    /// Memory-map a file written by `save` instead of reading it into memory.
+    ///
+    /// The file must only contain this table (see `map_at` for files with several tables), and it
+    /// must not be modified while the table is in use.
+    pub fn map(file: &File) -> Result<Self, LoadError> {
+        let header = Header::new::<C, M>(TableKind::Prune, C::COUNT);
+        let table = table_io::map_table(file, &header, 1)?;
+
+        Ok(Self {
+            table,
+            _phantoms: PhantomData,
+        })
+    }
+
+    /// Memory-map a table which `save` wrote `offset` bytes into a file.
+    ///
+    /// This is for files with several tables saved one after another. The offset of each one is
+    /// the position of the writer before saving it (e.g. from `Seek::stream_position`).
+    pub fn map_at(file: &File, offset: u64) -> Result<Self, LoadError> {
+        let header = Header::new::<C, M>(TableKind::Prune, C::COUNT);
+        let table = table_io::map_table_at(file, offset, &header, 1)?;
+
+        Ok(Self {
+            table,
+            _phantoms: PhantomData,
+        })
+    }
+}
+
+impl<P, C, M, D> FullPruneTable<C, M, D>
+where
+    P: PuzzlePerm,
+    C: Coord<P>,
+    M: PuzzleMove<Puzzle = P>,
+    D: AsRef<[u8]>,
+{
+    /// Write the table in the format described in `table_io`.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let table = self.table.as_ref();
+        let header = Header::new::<C, M>(TableKind::Prune, table.len());
+        table_io::write_table(&mut writer, &header, table)
+    }
+}
+
+/// An exact-valued pruning table for a composite coordinate, where the first sub-coordinate is
+/// reduced by symmetry.
+///
+/// The table is indexed by the equivalence class of the first sub-coordinate and the second
+/// sub-coordinate conjugated by the symmetry which takes the first one to its representant. This
+/// makes it roughly `S::Symmetry::COUNT` times smaller than the `FullPruneTable` for the same
+/// coordinate.
+///
+/// The second sub-coordinate must be preserved by the symmetries (see `SymConjTable`) and the move
+/// set must be closed under conjugation by them.
+pub struct SymPruneTable<P, S, C, M>
+where
+    P: PuzzlePerm,
+    S: SymCoord<P>,
+    C: CompositeCoord<P, CoordA = S::BaseCoord>,
+{
+    representants: RepresentantTable<P, S>,
+    conj_table: SymConjTable<C::CoordB, S::Symmetry>,
+    table: Box<[u8]>,
+    _phantoms: PhantomData<M>,
+}
+
+impl<P, S, C, M> PruneTable for SymPruneTable<P, S, C, M>
+where
+    P: PuzzlePerm,
+    S: SymCoord<P>,
+    C: Coord<P> + CompositeCoord<P, CoordA = S::BaseCoord>,
+    M: PuzzleMove<Puzzle = P>,
+{
+    type Puzzle = P;
+    type Coord = C;
+    type Move = M;
+
+    fn get_min_moves(&self, coord: C) -> u32 {
+        let (a, b) = coord.into_coords();
+        let (sym, equiv_class) = self.representants.get_equiv_class(a);
+        let b = self.conj_table.get_conj(b, sym);
+        self.table[equiv_class.index() * C::CoordB::COUNT + b.index()] as u32
+    }
+}
+
+impl<P, S, C, M> SymPruneTable<P, S, C, M>
+where
+    P: PuzzlePerm,
+    S: SymCoord<P>,
+    C: Coord<P> + CompositeCoord<P, CoordA = S::BaseCoord>,
+    M: PuzzleMove<Puzzle = P>,
+{
+    /// Create a pruning table from the move tables of the sub-coordinates.
+    ///
+    /// The target must be preserved by all of the symmetries (e.g. the solved state).
+    pub fn create<AT, BT>(
+        representants: RepresentantTable<P, S>,
+        table_a: &AT,
+        table_b: &BT,
+        target: C,
+    ) -> Self
+    where
+        AT: MoveTable<Puzzle = P, Coord = S::BaseCoord, Move = M>,
+        BT: MoveTable<Puzzle = P, Coord = C::CoordB, Move = M>,
+    {
+        info!("Building symmetry-reduced pruning table");
+
+        let class_count = representants.len();
+        let b_count = C::CoordB::COUNT;
+
+        let conj_table = SymConjTable::create();
+
+        // Symmetries which leave each representant unchanged. Entries of the same class related by
+        // one of these are equivalent, so they have to be filled in together.
+        let class_syms = S::EquivClass::iter()
+            .take(class_count)
+            .map(|class| {
+                representants
+                    .get_class_symmetries(class)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Apply each move to each representant up front since it is needed in every iteration
+        let class_moves = representants
+            .iter()
+            .flat_map(|repr| M::iter().map(move |m| table_a.get_move(repr, m)))
+            .map(|coord| representants.get_sym_coord(coord))
+            .collect::<Vec<_>>();
+
+        // Initialize with max to indicate unfilled
+        let mut table = vec![u8::MAX; class_count * b_count].into_boxed_slice();
+
+        // Fills in an entry and the ones equivalent to it, returning how many were filled
+        let fill = |table: &mut [u8], class: usize, b: C::CoordB, dist: u8| -> usize {
+            let mut filled = 0;
+            for sym in &class_syms[class] {
+                let index = class * b_count + conj_table.get_conj(b, *sym).index();
+                if table[index] == u8::MAX {
+                    table[index] = dist;
+                    filled += 1;
+                }
+            }
+            filled
+        };
+
+        // Returns the class and the conjugated second coordinate after applying a move
+        let apply_move = |class: usize, b: C::CoordB, m: M| -> (usize, C::CoordB) {
+            let new_a = class_moves[class * M::COUNT + m.index()];
+            let new_b = conj_table.get_conj(table_b.get_move(b, m), new_a.symmetry());
+            (new_a.equiv_class().index(), new_b)
+        };
+
+        // Fill in the target
+        let (target_a, target_b) = target.into_coords();
+        let (target_sym, target_class) = representants.get_equiv_class(target_a);
+        let mut remaining = class_count * b_count
+            - fill(
+                &mut table,
+                target_class.index(),
+                conj_table.get_conj(target_b, target_sym),
+                0,
+            );
+        let mut n = 0;
+
+        // Same algorithm as `FullPruneTable::create`, except that moves are applied to the
+        // representant of each class.
+
+        // Forward search
+        loop {
+            info!("Forward search at distance {}", n);
+
+            let mut filled = 0;
+
+            for class in 0..class_count {
+                for b in C::CoordB::iter() {
+                    if table[class * b_count + b.index()] == n {
+                        for m in M::iter() {
+                            let (new_class, new_b) = apply_move(class, b, m);
+                            filled += fill(&mut table, new_class, new_b, n + 1);
+                        }
+                    }
+                }
+            }
+
+            n += 1;
+            info!("{} filled at distance {}", filled, n);
+            remaining -= filled;
+
+            // (also exits if the rest can't be reached)
+            if remaining <= filled || filled == 0 {
+                break;
+            }
+        }
+
+        // Reverse search
+        while remaining > 0 {
+            info!("Reverse search at distance {}", n);
+
+            let mut filled = 0;
+
+            for class in 0..class_count {
+                for b in C::CoordB::iter() {
+                    if table[class * b_count + b.index()] == u8::MAX {
+                        let found = M::iter().any(|m| {
+                            let (new_class, new_b) = apply_move(class, b, m);
+                            table[new_class * b_count + new_b.index()] == n
+                        });
+
+                        if found {
+                            filled += fill(&mut table, class, b, n + 1);
+                        }
+                    }
+                }
+            }
+
+            n += 1;
+            info!("{} filled at distance {}", filled, n);
+            remaining -= filled;
+
+            if filled == 0 {
+                break;
+            }
+        }
+
+        Self {
+            representants,
+            conj_table,
+            table,
+            _phantoms: PhantomData,
+        }
+    }
+
+    /// Write the table in the format described in `table_io`.
+    ///
+    /// The representant table is written first, followed by the pruning table itself.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.representants.save(&mut writer)?;
+
+        let header = Header::new::<C, M>(TableKind::Prune, self.table.len());
+        table_io::write_table(&mut writer, &header, &self.table)
+    }
+
+    /// Read a table written by `save`.
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, LoadError>
+    where
+        S::BaseCoord: FromPrimitive,
+    {
+        let representants = RepresentantTable::load(&mut reader)?;
+
+        let len = representants.len() * C::CoordB::COUNT;
+        let header = Header::new::<C, M>(TableKind::Prune, len);
+        let table = table_io::read_table(&mut reader, &header, 1)?;
+
+        Ok(Self {
+            representants,
+            conj_table: SymConjTable::create(),
+            table: table.into_boxed_slice(),
+            _phantoms: PhantomData,
+        })
+    }
+}
+
+/// Pruning table for a composite coordinate.
+///
+/// This hold a pruning table for each sub-coordinate and returns the greatest lower bound between
+/// them. One of the sub-coordinate pruning tables can be `ZeroPruneTable` to ignore that coordinate
+/// when calculating the lower bound for the move count.
+///
+/// The `C` type parameter is a coordinate that can be converted into a composite coordinate.
+pub struct CompositePruneTable<'a, C, AT, BT> {
+    table_a: &'a AT,
+    table_b: &'a BT,
+    _coord: PhantomData<C>,
+}
+
+impl<'a, P, M, A, B, C, AT, BT> CompositePruneTable<'a, C, AT, BT>
+where
+    P: PuzzlePerm,
+    M: PuzzleMove<Puzzle = P>,
+    AT: PruneTable<Puzzle = P, Coord = A, Move = M>,
+    BT: PruneTable<Puzzle = P, Coord = B, Move = M>,
+    A: Coord<P>,
+    B: Coord<P>,
+    C: Coord<P> + CompositeCoord<P, CoordA = A, CoordB = B>,
+{
+    pub fn new(table_a: &'a AT, table_b: &'a BT) -> Self {
+        Self {
+            table_a,
+            table_b,
+            _coord: PhantomData,
+        }
+    }
+}
+
+impl<'a, P, M, A, B, C, AT, BT> PruneTable for CompositePruneTable<'a, C, AT, BT>
+where
+    P: PuzzlePerm,
+    M: PuzzleMove<Puzzle = P>,
+    AT: PruneTable<Puzzle = P, Coord = A, Move = M>,
+    BT: PruneTable<Puzzle = P, Coord = B, Move = M>,
+    A: Coord<P>,
+    B: Coord<P>,
+    C: Coord<P> + CompositeCoord<P, CoordA = A, CoordB = B>,
+{
+    type Puzzle = P;
+    type Coord = C;
+    type Move = M;
+
+    fn get_min_moves(&self, coord: C) -> u32 {
+        let (a, b) = coord.into_coords();
+        std::cmp::max(self.table_a.get_min_moves(a), self.table_b.get_min_moves(b))
+    }
+}
+
+/* TODO Move into cube-specific mod
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    use crate::coord::Corner7Coord;
+    use crate::cube::moves::UrfTurn;
+
+    lazy_static! {
+        pub static ref CORNER_PRUNE_TABLE: PruneTable<Corner7Coord, UrfTurn> = PruneTable::create(
+            &*crate::move_table::test::CORNER_MOVE_TABLE,
+            Corner7Coord::default(),
+        );
+    }
+
+    #[test]
+    fn table_is_created() {
+        assert_eq!(CORNER_PRUNE_TABLE.table.len(), 3674160);
+    }
+}
+*/
@@ -0,0 +1,497 @@
+//! Pruning tables which pack multiple entries into each byte.
+//!
+//! These take less memory than `FullPruneTable`, which makes it possible to use larger coordinates,
+//! at the cost of some precision or extra work per lookup.
+
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use num_traits::FromPrimitive;
+use rayon::ThreadPool;
+
+use crate::coord::Coord;
+use crate::move_table::MoveTable;
+use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::table_io::{self, Header, LoadError, TableKind};
+use crate::util::{EnumCount, EnumIndex, IntoEnumIterator};
+
+use super::{
+    fill_table, fill_table_parallel, get_entry, table_len, Encoding, PruneTable, UNREACHABLE,
+};
+
+/// Entries of 4 bits each, 2 per byte.
+struct Nibbles;
+
+impl Encoding for Nibbles {
+    const BITS: usize = 4;
+}
+
+/// Entries of 2 bits each, 4 per byte, storing the distance modulo 3.
+struct Mod3;
+
+impl Encoding for Mod3 {
+    const BITS: usize = 2;
+    const MAX_DIST: u8 = u8::MAX - 1;
+
+    fn encode(dist: u8) -> u8 {
+        dist % 3
+    }
+}
+
+/// Pruning table storing 4 bits per entry.
+///
+/// This is half the size of `FullPruneTable`. Distances are exact up to 14, and anything further
+/// is reported as 15, which is still a lower bound.
+///
+/// Entries which can't reach a target are also reported as 15, since there is no spare value to
+/// tell them apart from far away ones. Unlike `FullPruneTable`, this never returns `UNREACHABLE`,
+/// so searches using it can't tell that a target is unreachable and run until they hit a limit.
+pub struct PackedPruneTable<C, M> {
+    table: Box<[u8]>,
+    _phantoms: PhantomData<(C, M)>,
+}
+
+impl<P, C, M> PruneTable for PackedPruneTable<C, M>
+where
+    P: PuzzlePerm,
+    C: Coord<P>,
+    M: PuzzleMove<Puzzle = P>,
+{
+    type Puzzle = P;
+    type Coord = C;
+    type Move = M;
+
+    fn get_min_moves(&self, coord: C) -> u32 {
+        get_entry::<Nibbles>(&self.table, coord.index()) as u32
+    }
+}
+
+impl<P, C, M> PackedPruneTable<C, M>
+where
+    P: PuzzlePerm,
+    C: Coord<P>,
+    M: PuzzleMove<Puzzle = P>,
+{
+    /// Create a pruning table from a move table.
+    pub fn create<MT>(move_table: &MT, target: C) -> Self
+    where
+        MT: MoveTable<Puzzle = P, Coord = C, Move = M>,
+    {
+        Self::create_with_targets(move_table, &[target])
+    }
+
+    /// Create a pruning table giving the distance to the closest of a set of targets.
+    pub fn create_with_targets<MT>(move_table: &MT, targets: &[C]) -> Self
+    where
+        MT: MoveTable<Puzzle = P, Coord = C, Move = M>,
+    {
+        info!("Building packed pruning table");
+
+        let mut table = vec![u8::MAX; table_len::<Nibbles>(C::COUNT)].into_boxed_slice();
+        fill_table::<Nibbles, _>(&mut table, move_table, targets);
+
+        Self {
+            table,
+            _phantoms: PhantomData,
+        }
+    }
+
+    /// Create a pruning table like `create`, splitting the work between threads.
+    ///
+    /// The work is done in `pool` if given, or in the global thread pool otherwise.
+    pub fn create_parallel<MT>(move_table: &MT, target: C, pool: Option<&ThreadPool>) -> Self
+    where
+        MT: MoveTable<Puzzle = P, Coord = C, Move = M> + Sync,
+        C: FromPrimitive,
+    {
+        Self::create_parallel_with_targets(move_table, &[target], pool)
+    }
+
+    /// Create a pruning table like `create_with_targets`, splitting the work between threads.
+    pub fn create_parallel_with_targets<MT>(
+        move_table: &MT,
+        targets: &[C],
+        pool: Option<&ThreadPool>,
+    ) -> Self
+    where
+        MT: MoveTable<Puzzle = P, Coord = C, Move = M> + Sync,
+        C: FromPrimitive,
+    {
+        info!("Building packed pruning table in parallel");
+
+        let mut table = vec![u8::MAX; table_len::<Nibbles>(C::COUNT)].into_boxed_slice();
+        fill_table_parallel::<Nibbles, _>(&mut table, move_table, targets, pool);
+
+        Self {
+            table,
+            _phantoms: PhantomData,
+        }
+    }
+
+    /// Write the table in the format described in `table_io`.
+    ///
+    /// The length in the header is the number of bytes, not the number of entries.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let header = Header::new::<C, M>(TableKind::Prune, self.table.len());
+        table_io::write_table(&mut writer, &header, &self.table)
+    }
+
+    /// Read a table written by `save`.
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, LoadError> {
+        let header = Header::new::<C, M>(TableKind::Prune, table_len::<Nibbles>(C::COUNT));
+        let table = table_io::read_table(&mut reader, &header, 1)?;
+
+        Ok(Self {
+            table: table.into_boxed_slice(),
+            _phantoms: PhantomData,
+        })
+    }
+}
+
+/// Pruning table storing each distance modulo 3 in 2 bits per entry.
+///
+/// This is a quarter of the size of `FullPruneTable`, but still gives exact distances. A single
+/// move changes the distance by at most 1, so the distance of a coordinate can be recovered from
+/// the distance of the coordinate before the move (see `PruneTable::get_min_moves_from`). Getting
+/// the distance without that takes a walk to a target through the move table, which is much
+/// slower, so this is only meant to be used for the root of a search.
+///
+/// The move set must be closed under inverses.
+pub struct Mod3PruneTable<'a, MT: MoveTable> {
+    move_table: &'a MT,
+    targets: Box<[MT::Coord]>,
+    table: Box<[u8]>,
+}
+
+impl<'a, MT: MoveTable> PruneTable for Mod3PruneTable<'a, MT> {
+    type Puzzle = MT::Puzzle;
+    type Coord = MT::Coord;
+    type Move = MT::Move;
+
+    fn get_min_moves(&self, coord: MT::Coord) -> u32 {
+        let mut coord = coord;
+        let mut value = get_entry::<Mod3>(&self.table, coord.index());
+        if value == Mod3::UNFILLED {
+            return u32::MAX;
+        }
+
+        // Neighbors are at most 1 move closer or further, so the one with a value of 1 less
+        // (modulo 3) is closer to the target
+        let mut dist = 0;
+        while !self.targets.contains(&coord) {
+            let closer = (value + 2) % 3;
+            coord = MT::Move::iter()
+                .map(|m| self.move_table.get_move(coord, m))
+                .find(|c| get_entry::<Mod3>(&self.table, c.index()) == closer)
+                .expect("filled entries which are not targets have a closer neighbor");
+            value = closer;
+            dist += 1;
+        }
+
+        dist
+    }
+
+    fn get_min_moves_from(&self, coord: MT::Coord, parent_moves: u32) -> u32 {
+        let value = get_entry::<Mod3>(&self.table, coord.index()) as u32;
+        // Neighbors of unreachable coordinates are unreachable too, since the move set is closed
+        // under inverses
+        if value == Mod3::UNFILLED as u32 || parent_moves >= UNREACHABLE {
+            u32::MAX
+        } else if value == (parent_moves + 2) % 3 {
+            parent_moves.saturating_sub(1)
+        } else if value == parent_moves % 3 {
+            parent_moves
+        } else {
+            parent_moves.saturating_add(1)
+        }
+    }
+}
+
+impl<'a, MT: MoveTable> Mod3PruneTable<'a, MT> {
+    /// Create a pruning table from a move table.
+    ///
+    /// The move table is kept to recover exact distances.
+    pub fn create(move_table: &'a MT, target: MT::Coord) -> Self {
+        Self::create_with_targets(move_table, &[target])
+    }
+
+    /// Create a pruning table giving the distance to the closest of a set of targets.
+    pub fn create_with_targets(move_table: &'a MT, targets: &[MT::Coord]) -> Self {
+        info!("Building mod 3 pruning table");
+
+        let mut table = vec![u8::MAX; table_len::<Mod3>(MT::Coord::COUNT)].into_boxed_slice();
+        fill_table::<Mod3, _>(&mut table, move_table, targets);
+
+        Self {
+            move_table,
+            targets: targets.into(),
+            table,
+        }
+    }
+
+    /// Create a pruning table like `create`, splitting the work between threads.
+    ///
+    /// The work is done in `pool` if given, or in the global thread pool otherwise.
+    pub fn create_parallel(move_table: &'a MT, target: MT::Coord, pool: Option<&ThreadPool>) -> Self
+    where
+        MT: Sync,
+        MT::Coord: FromPrimitive,
+    {
+        Self::create_parallel_with_targets(move_table, &[target], pool)
+    }
+
+    /// Create a pruning table like `create_with_targets`, splitting the work between threads.
+    pub fn create_parallel_with_targets(
+        move_table: &'a MT,
+        targets: &[MT::Coord],
+        pool: Option<&ThreadPool>,
+    ) -> Self
+    where
+        MT: Sync,
+        MT::Coord: FromPrimitive,
+    {
+        info!("Building mod 3 pruning table in parallel");
+
+        let mut table = vec![u8::MAX; table_len::<Mod3>(MT::Coord::COUNT)].into_boxed_slice();
+        fill_table_parallel::<Mod3, _>(&mut table, move_table, targets, pool);
+
+        Self {
+            move_table,
+            targets: targets.into(),
+            table,
+        }
+    }
+
+    /// Write the table in the format described in `table_io`.
+    ///
+    /// The length in the header is the number of bytes, not the number of entries.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let header = Header::new::<MT::Coord, MT::Move>(TableKind::Prune, self.table.len());
+        table_io::write_table(&mut writer, &header, &self.table)
+    }
+
+    /// Read a table written by `save`.
+    ///
+    /// The move table and targets must be the same as the ones the table was created with.
+    pub fn load<R: Read>(
+        mut reader: R,
+        move_table: &'a MT,
+        targets: &[MT::Coord],
+    ) -> Result<Self, LoadError> {
+        let header = Header::new::<MT::Coord, MT::Move>(
+            TableKind::Prune,
+            table_len::<Mod3>(MT::Coord::COUNT),
+        );
+        let table = table_io::read_table(&mut reader, &header, 1)?;
+
+        Ok(Self {
+            move_table,
+            targets: targets.into(),
+            table: table.into_boxed_slice(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::cube::cube3::coord::{CornerPosAndESliceCoord, CornerPosCoord, ESliceEdgePosCoord};
+    use crate::cube::cube3::{Cube3Perm, G1CubeTurn};
+    use crate::move_table::{BasicMoveTable, CompositeMoveTable};
+    use crate::prune_table::FullPruneTable;
+    use crate::solver::SolutionIter;
+    use rayon::ThreadPoolBuilder;
+
+    type CornerTable = BasicMoveTable<CornerPosCoord, G1CubeTurn>;
+    type ESliceTable = BasicMoveTable<ESliceEdgePosCoord, G1CubeTurn>;
+
+    lazy_static! {
+        static ref CORNER_TABLE: CornerTable = BasicMoveTable::create();
+        static ref ESLICE_TABLE: ESliceTable = BasicMoveTable::create();
+        static ref MOVE_TABLE: CompositeMoveTable<'static, CornerPosAndESliceCoord, CornerTable, ESliceTable> =
+            CompositeMoveTable::new(&*CORNER_TABLE, &*ESLICE_TABLE);
+        static ref FULL_TABLE: FullPruneTable<CornerPosAndESliceCoord, G1CubeTurn> =
+            FullPruneTable::create(&*MOVE_TABLE, CornerPosAndESliceCoord::default());
+    }
+
+    #[test]
+    fn packed_table_matches_full_table() {
+        let packed_table = PackedPruneTable::create(&*MOVE_TABLE, Default::default());
+
+        for coord in CornerPosAndESliceCoord::iter() {
+            assert_eq!(
+                packed_table.get_min_moves(coord),
+                std::cmp::min(FULL_TABLE.get_min_moves(coord), 15),
+                "Different bounds for {:?}",
+                coord
+            );
+        }
+    }
+
+    #[test]
+    fn mod3_table_matches_full_table() {
+        let mod3_table = Mod3PruneTable::create(&*MOVE_TABLE, Default::default());
+
+        // Walking to the target takes a while
+        for coord in CornerPosAndESliceCoord::iter().step_by(97) {
+            let moves = FULL_TABLE.get_min_moves(coord);
+            assert_eq!(
+                mod3_table.get_min_moves(coord),
+                moves,
+                "Different bounds for {:?}",
+                coord
+            );
+
+            for m in G1CubeTurn::iter() {
+                let next = MOVE_TABLE.get_move(coord, m);
+                assert_eq!(
+                    mod3_table.get_min_moves_from(next, moves),
+                    FULL_TABLE.get_min_moves(next),
+                    "Different bounds for {:?} after {:?}",
+                    coord,
+                    m
+                );
+            }
+        }
+
+        // Coordinates next to unreachable ones are unreachable too
+        let coord = CornerPosAndESliceCoord::default();
+        assert_eq!(mod3_table.get_min_moves_from(coord, u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn packed_table_round_trip() {
+        let packed_table = PackedPruneTable::create(&*MOVE_TABLE, Default::default());
+        let mut buf = Vec::new();
+        packed_table.save(&mut buf).unwrap();
+
+        let loaded =
+            PackedPruneTable::<CornerPosAndESliceCoord, G1CubeTurn>::load(&buf[..]).unwrap();
+        assert_eq!(loaded.table, packed_table.table);
+    }
+
+    fn save_bytes(save: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        save(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn parallel_tables_match_sequential_tables() {
+        let target = CornerPosAndESliceCoord::default();
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+
+        let full_table = FullPruneTable::create_parallel(&*MOVE_TABLE, target, Some(&pool));
+        assert_eq!(
+            save_bytes(|buf| full_table.save(buf)),
+            save_bytes(|buf| FULL_TABLE.save(buf)),
+        );
+
+        let packed_table = PackedPruneTable::create_parallel(&*MOVE_TABLE, target, Some(&pool));
+        let sequential_table = PackedPruneTable::create(&*MOVE_TABLE, target);
+        assert_eq!(
+            save_bytes(|buf| packed_table.save(buf)),
+            save_bytes(|buf| sequential_table.save(buf)),
+        );
+
+        let mod3_table = Mod3PruneTable::create_parallel(&*MOVE_TABLE, target, None);
+        let sequential_table = Mod3PruneTable::create(&*MOVE_TABLE, target);
+        assert_eq!(
+            save_bytes(|buf| mod3_table.save(buf)),
+            save_bytes(|buf| sequential_table.save(buf)),
+        );
+    }
+
+    #[test]
+    fn parallel_multi_target_tables_match_sequential_tables() {
+        let targets = [
+            CornerPosAndESliceCoord::default(),
+            CornerPosAndESliceCoord::from_perm(G1CubeTurn::R2.permutation()),
+            CornerPosAndESliceCoord::from_perm(G1CubeTurn::U.permutation()),
+        ];
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+
+        let full_table =
+            FullPruneTable::create_parallel_with_targets(&*MOVE_TABLE, &targets, Some(&pool));
+        let sequential_table = FullPruneTable::create_with_targets(&*MOVE_TABLE, &targets);
+        assert_eq!(
+            save_bytes(|buf| full_table.save(buf)),
+            save_bytes(|buf| sequential_table.save(buf)),
+        );
+
+        let packed_table =
+            PackedPruneTable::create_parallel_with_targets(&*MOVE_TABLE, &targets, None);
+        let sequential_table = PackedPruneTable::create_with_targets(&*MOVE_TABLE, &targets);
+        assert_eq!(
+            save_bytes(|buf| packed_table.save(buf)),
+            save_bytes(|buf| sequential_table.save(buf)),
+        );
+
+        let mod3_table =
+            Mod3PruneTable::create_parallel_with_targets(&*MOVE_TABLE, &targets, Some(&pool));
+        let sequential_table = Mod3PruneTable::create_with_targets(&*MOVE_TABLE, &targets);
+        assert_eq!(
+            save_bytes(|buf| mod3_table.save(buf)),
+            save_bytes(|buf| sequential_table.save(buf)),
+        );
+    }
+
+    #[test]
+    fn multi_target_tables_match_sub_coordinate_table() {
+        // Solving the corners with the E slice edges in any position is the same as solving just
+        // the corners
+        let corner_table = FullPruneTable::create(&*CORNER_TABLE, CornerPosCoord::default());
+        let targets = ESliceEdgePosCoord::iter()
+            .map(|e_edges| CornerPosAndESliceCoord::new(CornerPosCoord::default(), e_edges))
+            .collect::<Vec<_>>();
+
+        let full_table = FullPruneTable::create_with_targets(&*MOVE_TABLE, &targets);
+        let mod3_table = Mod3PruneTable::create_with_targets(&*MOVE_TABLE, &targets);
+
+        for coord in CornerPosAndESliceCoord::iter().step_by(97) {
+            let moves = corner_table.get_min_moves(coord.corners());
+            assert_eq!(full_table.get_min_moves(coord), moves);
+            assert_eq!(mod3_table.get_min_moves(coord), moves);
+        }
+
+        let perm = [G1CubeTurn::R2, G1CubeTurn::U, G1CubeTurn::F2]
+            .iter()
+            .fold(Cube3Perm::identity().clone(), |perm, m| {
+                perm.sequence(m.permutation())
+            });
+        let solution = SolutionIter::with_goal(&*MOVE_TABLE, &full_table, &targets[..], &perm)
+            .next()
+            .unwrap();
+        let solved = solution
+            .iter()
+            .fold(perm.clone(), |perm, m| perm.sequence(m.permutation()));
+        assert!(targets.contains(&CornerPosAndESliceCoord::from_perm(&solved)));
+        assert_eq!(
+            solution.len() as u32,
+            corner_table.get_min_moves(CornerPosCoord::from_perm(&perm))
+        );
+    }
+
+    #[test]
+    fn solution_iter_uses_mod3_table() {
+        use G1CubeTurn::*;
+
+        let mod3_table = Mod3PruneTable::create(&*MOVE_TABLE, Default::default());
+        let perm = [R2, U, F2, DP, L2, B2, U2, R2, D, F2]
+            .iter()
+            .fold(Cube3Perm::identity().clone(), |perm, m| {
+                perm.sequence(m.permutation())
+            });
+
+        let expected = SolutionIter::new(&*MOVE_TABLE, &*FULL_TABLE, Default::default(), &perm)
+            .take(10)
+            .map(|solution| solution.len())
+            .collect::<Vec<_>>();
+        let found = SolutionIter::new(&*MOVE_TABLE, &mod3_table, Default::default(), &perm)
+            .take(10)
+            .map(|solution| solution.len())
+            .collect::<Vec<_>>();
+        assert_eq!(found, expected);
+    }
+}
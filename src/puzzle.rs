@@ -62,6 +62,9 @@ pub trait PuzzlePerm: 'static + Clone + Eq {
 pub trait PuzzleMove: EnumIndex {
     type Puzzle: PuzzlePerm;
 
+    /// Name identifying the move set in saved tables, like `Coord::NAME`.
+    const NAME: &'static str;
+
     /// Permutation associated with this move
     fn permutation(&self) -> &Self::Puzzle;
 
@@ -91,7 +94,8 @@ macro_rules! impl_puzzle_perm_with_tables {
                 use $crate::util::{EnumCount, EnumIndex, IntoEnumIterator};
                 lazy_static! {
                     static ref TABLE: [$impl_ty; <$impl_ty>::COUNT] = {
-                        let mut table: [$impl_ty; <$impl_ty>::COUNT] = Default::default();
+                        let mut table: [$impl_ty; <$impl_ty>::COUNT] =
+                            std::array::from_fn(|_| ($identity_closure)().clone());
 
                         for sym in <$impl_ty>::iter() {
                             let perm = ($prem_closure)(&sym);
@@ -114,7 +118,9 @@ macro_rules! impl_puzzle_perm_with_tables {
                 lazy_static! {
                     static ref TABLE: [[$impl_ty; <$impl_ty>::COUNT]; <$impl_ty>::COUNT] = {
                         let mut table: [[$impl_ty; <$impl_ty>::COUNT]; <$impl_ty>::COUNT] =
-                            Default::default();
+                            std::array::from_fn(|_| {
+                                std::array::from_fn(|_| ($identity_closure)().clone())
+                            });
 
                         for sym in <$impl_ty>::iter() {
                             let perm = ($prem_closure)(&sym);
@@ -0,0 +1,127 @@
+//! Limits for stopping searches early.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Flag for cancelling a search from another thread.
+///
+/// Clones share the same flag, so one can be kept to cancel a search which was given another.
+#[derive(Clone, Debug, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel all searches using this flag.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits for a search, and a flag for cancelling it.
+///
+/// The deadline and cancellation flag are only checked every `CHECK_INTERVAL` nodes, and parallel
+/// searches only count nodes in batches of that size, so searches can go slightly past the limits.
+#[derive(Clone, Debug, Default)]
+pub struct SearchControl {
+    /// Maximum number of moves in a solution.
+    pub max_depth: Option<u32>,
+
+    /// Maximum number of nodes to visit.
+    pub max_nodes: Option<u64>,
+
+    /// Time to stop the search at.
+    pub deadline: Option<Instant>,
+
+    /// Flag for cancelling the search from elsewhere.
+    pub cancel: CancelFlag,
+}
+
+impl SearchControl {
+    /// Number of nodes between checks of the deadline and cancellation flag.
+    pub const CHECK_INTERVAL: u64 = 1024;
+
+    /// Check the node limit after visiting `nodes` nodes.
+    pub(crate) fn check_nodes(&self, nodes: u64) -> Result<(), AbortReason> {
+        if self.max_nodes.is_some_and(|max| nodes > max) {
+            Err(AbortReason::MaxNodes)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the deadline and cancellation flag.
+    pub(crate) fn check_interrupt(&self) -> Result<(), AbortReason> {
+        if self.cancel.is_cancelled() {
+            Err(AbortReason::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Err(AbortReason::Deadline)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the limits before searching for solutions of length `depth`.
+    pub(crate) fn check_depth(&self, depth: u32) -> Result<(), AbortReason> {
+        if self.max_depth.is_some_and(|max| depth > max) {
+            Err(AbortReason::MaxDepth)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reason a search stopped without finding a solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbortReason {
+    /// All solutions up to the maximum depth have been found.
+    MaxDepth,
+    MaxNodes,
+    Deadline,
+    Cancelled,
+    /// There are no more solutions.
+    Exhausted,
+}
+
+/// Result of a search which was stopped early.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Aborted {
+    pub reason: AbortReason,
+
+    /// Depth being searched when the search stopped.
+    pub depth: u32,
+
+    /// Number of nodes visited.
+    pub nodes: u64,
+}
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.reason {
+            AbortReason::MaxDepth => "reached the maximum depth",
+            AbortReason::MaxNodes => "reached the maximum number of nodes",
+            AbortReason::Deadline => "reached the deadline",
+            AbortReason::Cancelled => "was cancelled",
+            AbortReason::Exhausted => "ran out of states",
+        };
+        write!(
+            f,
+            "search {} at depth {} after {} nodes",
+            reason, self.depth, self.nodes
+        )
+    }
+}
+
+impl Error for Aborted {}
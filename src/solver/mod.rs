@@ -0,0 +1,883 @@
+//! IDA* solving algorithm using move tables
+
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use crate::coord::Coord;
+use crate::move_table::{InverseMoveTable, MoveTable};
+use crate::prune_table::{PruneTable, UNREACHABLE};
+use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::util::IntoEnumIterator;
+
+mod control;
+mod parallel;
+mod stats;
+
+pub use control::{AbortReason, Aborted, CancelFlag, SearchControl};
+pub use parallel::solve_parallel;
+pub use stats::{DepthStats, SearchStats};
+
+/// Condition for where a search ends.
+///
+/// This is implemented for collections of coordinates, where any of them is a goal, and for
+/// closures, which can check things like "EO solved on any axis".
+///
+/// The pruning table used with a goal should give the distance to the closest coordinate which
+/// meets it (see `FullPruneTable::create_with_targets`), or at least a lower bound for it.
+pub trait Goal<C> {
+    /// Whether a coordinate meets the goal.
+    fn is_goal(&self, coord: C) -> bool;
+}
+
+impl<C: Eq, const N: usize> Goal<C> for [C; N] {
+    fn is_goal(&self, coord: C) -> bool {
+        self.contains(&coord)
+    }
+}
+
+impl<C: Eq> Goal<C> for &[C] {
+    fn is_goal(&self, coord: C) -> bool {
+        self.contains(&coord)
+    }
+}
+
+impl<C: Eq> Goal<C> for Vec<C> {
+    fn is_goal(&self, coord: C) -> bool {
+        self.contains(&coord)
+    }
+}
+
+impl<C: Ord> Goal<C> for BTreeSet<C> {
+    fn is_goal(&self, coord: C) -> bool {
+        self.contains(&coord)
+    }
+}
+
+impl<C, F: Fn(C) -> bool> Goal<C> for F {
+    fn is_goal(&self, coord: C) -> bool {
+        self(coord)
+    }
+}
+
+/// Inverse of the puzzle state, for checking the pruning table with it as well.
+#[derive(Clone, Copy, Debug)]
+struct InverseState<C> {
+    coord: C,
+    /// Lower bound from the pruning table
+    bound: u32,
+}
+
+#[derive(Clone, Debug)]
+struct StackState<C, M: IntoEnumIterator> {
+    coord: C,
+    mov: M,
+    /// Lower bound from the pruning table
+    bound: u32,
+    inverse: Option<InverseState<C>>,
+    move_iter: M::Iterator,
+}
+
+impl<C, M: IntoEnumIterator> StackState<C, M> {
+    fn new(coord: C, mov: M, bound: u32, inverse: Option<InverseState<C>>) -> Self {
+        Self {
+            coord,
+            mov,
+            bound,
+            inverse,
+            move_iter: M::iter(),
+        }
+    }
+}
+
+/// Iterator producing puzzle solutions using IDA*.
+///
+/// Solutions are returned in order by length, shortest first (however solutions of equal length
+/// have no specified order).
+///
+/// The search can be limited with a `SearchControl`, in which case the iterator ends early and
+/// `aborted` says why. The work done at each depth is counted in `stats`.
+pub struct SolutionIter<'a, MT, PT, G = [<MT as MoveTable>::Coord; 1]>
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+    G: Goal<MT::Coord>,
+{
+    move_table: &'a MT,
+    prune_table: &'a PT,
+    goal: G,
+    init_perm: MT::Puzzle,
+    init_coord: MT::Coord,
+    init_bound: u32,
+    init_inverse: Option<InverseState<MT::Coord>>,
+    /// Table for moving the inverse states, if they are being checked
+    inverse_table: Option<&'a InverseMoveTable<MT::Coord, MT::Move>>,
+    /// Whether the initial state has been checked, which is the only solution at depth 0
+    init_checked: bool,
+    max_depth: u32,
+    next_max_depth: u32,
+    first_move_iter: <MT::Move as IntoEnumIterator>::Iterator,
+    stack: Vec<StackState<MT::Coord, MT::Move>>,
+    control: SearchControl,
+    nodes: u64,
+    aborted: Option<Aborted>,
+    stats: SearchStats,
+    /// Start of the time not yet added to `stats`
+    timer: Instant,
+}
+
+fn consume_iter<I: Iterator>(mut iter: I) -> I {
+    iter.find(|_| false);
+    iter
+}
+
+impl<'a, MT, PT> SolutionIter<'a, MT, PT>
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+{
+    pub fn new(
+        move_table: &'a MT,
+        prune_table: &'a PT,
+        target: MT::Coord,
+        perm: &MT::Puzzle,
+    ) -> Self {
+        Self::with_goal(move_table, prune_table, [target], perm)
+    }
+
+    /// Also check the pruning table with the inverse of each state, using the larger bound.
+    ///
+    /// The inverse of a state is solved by inverting its solutions, so it is the same distance from
+    /// being solved. This only holds if the coordinate covers a whole group (e.g. all of the
+    /// corners) and the target is its solved state, so this panics for any other target. For other
+    /// goals, like the G1 subgroup, the inverse can give bounds which are too large and cause
+    /// solutions to be missed. The pruning table can still be for smaller coordinates, like with
+    /// `CompositePruneTable`, which is where this helps: the bounds for a state and its inverse are
+    /// different even though their distances are the same.
+    ///
+    /// The inverse coordinate is moved with `inverse_table` along with the regular one, so this
+    /// only costs an extra lookup in each table per node, but can prune many more nodes.
+    pub fn with_inverse_lookups(
+        mut self,
+        inverse_table: &'a InverseMoveTable<MT::Coord, MT::Move>,
+    ) -> Self {
+        let solved = MT::Coord::from_perm(MT::Puzzle::identity());
+        assert!(
+            self.goal[0] == solved,
+            "Inverse lookups are only valid when the target is the solved state"
+        );
+
+        let coord = MT::Coord::from_perm(&self.init_perm.invert());
+        self.init_inverse = Some(InverseState {
+            coord,
+            bound: self.prune_table.get_min_moves(coord),
+        });
+        self.inverse_table = Some(inverse_table);
+        self
+    }
+}
+
+impl<'a, MT, PT, G> SolutionIter<'a, MT, PT, G>
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+    G: Goal<MT::Coord>,
+{
+    /// Create an iterator for solutions which end at any coordinate meeting a goal.
+    pub fn with_goal(move_table: &'a MT, prune_table: &'a PT, goal: G, perm: &MT::Puzzle) -> Self {
+        let init_coord = MT::Coord::from_perm(perm);
+        Self {
+            move_table,
+            prune_table,
+            goal,
+            init_perm: perm.clone(),
+            init_coord,
+            init_bound: prune_table.get_min_moves(init_coord),
+            init_inverse: None,
+            inverse_table: None,
+            init_checked: false,
+            max_depth: 0,
+            next_max_depth: 1,
+            // Consume this at first so that the 0-depth solution (i.e. initial permutation is
+            // already at the target) will be chedked.
+            first_move_iter: consume_iter(MT::Move::iter()),
+            stack: Vec::new(),
+            control: SearchControl::default(),
+            nodes: 0,
+            aborted: None,
+            stats: SearchStats::default(),
+            timer: Instant::now(),
+        }
+    }
+
+    /// Limit the search.
+    pub fn with_control(mut self, control: SearchControl) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Returns why the search stopped, if it was stopped early.
+    pub fn aborted(&self) -> Option<Aborted> {
+        self.aborted
+    }
+
+    /// Returns the work done by the search so far.
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /// Add the time since the timer was started to the current depth.
+    fn record_time(&mut self) {
+        let now = Instant::now();
+        if let Some(depth) = self.stats.depths.last_mut() {
+            depth.time += now - self.timer;
+        }
+        self.timer = now;
+    }
+
+    /// Finish the stats for the last depth and start them for `max_depth`.
+    fn start_depth(&mut self) {
+        self.record_time();
+        if let Some(depth) = self.stats.depths.last() {
+            debug!("Search {}", depth);
+        }
+        self.stats.depths.push(DepthStats {
+            depth: self.max_depth,
+            ..Default::default()
+        });
+    }
+
+    /// Apply a move to the inverse state, if there is one.
+    ///
+    /// The move comes after the state, so its inverse comes before the inverse state. The pruning
+    /// table is not given the previous bound, since only the regular states' bounds are exact for
+    /// tables like `Mod3PruneTable`.
+    fn next_inverse(
+        &self,
+        inverse: Option<&InverseState<MT::Coord>>,
+        mov: MT::Move,
+    ) -> Option<InverseState<MT::Coord>> {
+        let inverse = inverse?;
+        let coord = self.inverse_table?.get_move(inverse.coord, mov);
+        let bound = self.prune_table.get_min_moves(coord);
+        Some(InverseState { coord, bound })
+    }
+
+    /// Count a visited node and check the limits.
+    fn visit_node(&mut self) -> Result<(), AbortReason> {
+        self.nodes += 1;
+        self.control.check_nodes(self.nodes)?;
+        if self.nodes.is_multiple_of(SearchControl::CHECK_INTERVAL) {
+            self.control.check_interrupt()?;
+        }
+        Ok(())
+    }
+
+    fn abort(&mut self, reason: AbortReason) -> Option<Vec<MT::Move>> {
+        self.aborted = Some(Aborted {
+            reason,
+            depth: self.max_depth,
+            nodes: self.nodes,
+        });
+        self.stack.clear();
+        None
+    }
+}
+
+impl<'a, MT, PT, G> SolutionIter<'a, MT, PT, G>
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+    G: Goal<MT::Coord>,
+{
+    fn search(&mut self) -> Option<Vec<MT::Move>> {
+        // Keep trying until a solution is found
+        loop {
+            let next_state = self.stack.last_mut().map(|state| {
+                let next_move = state.move_iter.next();
+                next_move.map(|m| (state.coord, state.mov, state.bound, m))
+            });
+
+            match next_state {
+                Some(Some((coord, this_move, bound, next_move))) => {
+                    // If the new move combines with the old one, there is another (single) move which
+                    // is their combination
+                    if !this_move.combines_with(&next_move) {
+                        if let Err(reason) = self.visit_node() {
+                            return self.abort(reason);
+                        }
+
+                        let new_coord = self.move_table.get_move(coord, next_move);
+                        self.stats.current_mut().lookups += 1;
+
+                        // Current depth is stack.len(), but we want the depth after adding another move
+                        let depth = self.stack.len() as u32 + 1;
+                        let new_bound = self.prune_table.get_min_moves_from(new_coord, bound);
+                        let new_inverse = self.next_inverse(
+                            self.stack.last().and_then(|state| state.inverse.as_ref()),
+                            next_move,
+                        );
+                        let heuristic = new_inverse
+                            .as_ref()
+                            .map_or(new_bound, |inverse| new_bound.max(inverse.bound));
+                        let heuristic_depth = heuristic.saturating_add(depth);
+                        if heuristic_depth <= self.max_depth {
+                            self.stats.current_mut().expanded += 1;
+
+                            // Add a new element for this move and keep going
+                            self.stack.push(StackState::new(
+                                new_coord,
+                                next_move,
+                                new_bound,
+                                new_inverse,
+                            ));
+
+                            // Only return a solution if it is for the current depth (otherwise it has
+                            // been returned before)
+                            // TODO This will fail to return solutions if the heuristic is not a strict
+                            // lower bound
+                            if depth == self.max_depth && self.goal.is_goal(new_coord) {
+                                return Some(self.stack.iter().map(|state| state.mov).collect());
+                            }
+                        } else {
+                            self.stats.current_mut().pruned += 1;
+
+                            // Next max depth is the smallest estimated depth after the current max
+                            // depth that will have a solution. States which can't reach the goal
+                            // won't have one at any depth.
+                            if heuristic < UNREACHABLE {
+                                self.next_max_depth =
+                                    std::cmp::min(self.next_max_depth, heuristic_depth);
+                            }
+                        }
+                    }
+                }
+                Some(None) => {
+                    // No more moves in this state, so pop it off the stack
+                    self.stack.pop();
+                }
+                None => {
+                    // Stack is empty
+                    match self.first_move_iter.next() {
+                        Some(first_move) => {
+                            if let Err(reason) = self.visit_node() {
+                                return self.abort(reason);
+                            }
+
+                            let new_coord = self.move_table.get_move(self.init_coord, first_move);
+                            let stats = self.stats.current_mut();
+                            stats.lookups += 1;
+                            stats.expanded += 1;
+
+                            let new_bound = self
+                                .prune_table
+                                .get_min_moves_from(new_coord, self.init_bound);
+                            let new_inverse =
+                                self.next_inverse(self.init_inverse.as_ref(), first_move);
+                            self.stack.push(StackState::new(
+                                new_coord,
+                                first_move,
+                                new_bound,
+                                new_inverse,
+                            ));
+
+                            // Only return a solution if it is for the current depth (otherwise it has
+                            // been returned before). The first iteration always has a maximum depth
+                            // of 1, so that is where single-move solutions are returned.
+                            if self.max_depth == 1 && self.goal.is_goal(new_coord) {
+                                return Some(self.stack.iter().map(|state| state.mov).collect());
+                            }
+                        }
+                        None => {
+                            // Before the first iteration, check if the initial state is already at
+                            // the target. This doesn't take any moves, so it is within any depth
+                            // limit.
+                            if !self.init_checked {
+                                self.init_checked = true;
+                                if self.goal.is_goal(self.init_coord) {
+                                    return Some(Vec::new());
+                                }
+                                continue;
+                            }
+
+                            // Only unreachable states were pruned
+                            if self.next_max_depth == u32::MAX {
+                                return self.abort(AbortReason::Exhausted);
+                            }
+
+                            // No more moves to try, so increase the depth
+                            self.max_depth = self.next_max_depth;
+                            self.next_max_depth = u32::MAX;
+                            self.first_move_iter = MT::Move::iter();
+
+                            if let Err(reason) = self.control.check_depth(self.max_depth) {
+                                return self.abort(reason);
+                            }
+                            self.start_depth();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, MT, PT, G> Iterator for SolutionIter<'a, MT, PT, G>
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+    G: Goal<MT::Coord>,
+{
+    type Item = Vec<MT::Move>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.aborted.is_some() {
+            return None;
+        }
+
+        // Don't count the time spent between solutions
+        self.timer = Instant::now();
+        let solution = self.search();
+        self.record_time();
+        solution
+    }
+}
+
+/// Find a shortest solution.
+///
+/// This is the same as the first solution from `SolutionIter`.
+pub fn solve_cube<MT, PT>(
+    move_table: &MT,
+    prune_table: &PT,
+    perm: &MT::Puzzle,
+    target: MT::Coord,
+    control: &SearchControl,
+) -> Result<Vec<MT::Move>, Aborted>
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+{
+    solve_cube_with_stats(move_table, prune_table, perm, target, control).0
+}
+
+/// Find a shortest solution, also returning the work done to find it.
+pub fn solve_cube_with_stats<MT, PT>(
+    move_table: &MT,
+    prune_table: &PT,
+    perm: &MT::Puzzle,
+    target: MT::Coord,
+    control: &SearchControl,
+) -> (Result<Vec<MT::Move>, Aborted>, SearchStats)
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+{
+    let mut solutions =
+        SolutionIter::new(move_table, prune_table, target, perm).with_control(control.clone());
+    // The iterator only ends early when it is aborted
+    let result = solutions.next().ok_or_else(|| solutions.aborted().unwrap());
+    (result, solutions.stats)
+}
+
+/// Find a shortest solution for the whole puzzle, not just for a coordinate.
+///
+/// This searches for solutions which reach the solved coordinate, and returns the first one which
+/// also solves the rest of the puzzle. Since all solutions for the puzzle are also solutions for
+/// the coordinate, it is the shortest one. The pruning table only has to be for the coordinate, so
+/// it can be much smaller than a table for the whole puzzle would be.
+///
+/// This doesn't check inverse states, since the coordinate usually doesn't cover a whole group (see
+/// `SolutionIter::with_inverse_lookups`).
+pub fn solve_puzzle<MT, PT>(
+    move_table: &MT,
+    prune_table: &PT,
+    perm: &MT::Puzzle,
+    control: &SearchControl,
+) -> Result<Vec<MT::Move>, Aborted>
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+{
+    let target = MT::Coord::from_perm(MT::Puzzle::identity());
+    let mut solutions =
+        SolutionIter::new(move_table, prune_table, target, perm).with_control(control.clone());
+
+    let solution = solutions.find(|moves| {
+        moves
+            .iter()
+            .fold(perm.clone(), |perm, m| perm.sequence(m.permutation()))
+            .is_identity()
+    });
+    // The iterator only ends early when it is aborted
+    solution.ok_or_else(|| solutions.aborted().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::cube::cube3::coord::{CornerPosAndESliceCoord, CornerPosCoord, ESliceEdgePosCoord};
+    use crate::cube::cube3::{Cube3Perm, G1CubeTurn};
+    use crate::move_table::{BasicMoveTable, CompositeMoveTable, InverseMoveTable};
+    use crate::prune_table::{CompositePruneTable, FullPruneTable, ZeroPruneTable};
+    use crate::puzzle::PuzzlePerm;
+    use crate::util::EnumIndex;
+    use rayon::ThreadPoolBuilder;
+
+    fn apply(perm: &Cube3Perm, moves: &[G1CubeTurn]) -> Cube3Perm {
+        moves
+            .iter()
+            .fold(perm.clone(), |perm, m| perm.sequence(m.permutation()))
+    }
+
+    #[test]
+    fn goals_match_coordinates() {
+        let coord = CornerPosCoord::default();
+        let other = CornerPosCoord::iter().nth(1).unwrap();
+
+        assert!([coord].is_goal(coord));
+        assert!(![other].is_goal(coord));
+        assert!(vec![other, coord].is_goal(coord));
+        assert!(!(&[other][..]).is_goal(coord));
+        assert!(BTreeSet::from([coord]).is_goal(coord));
+        assert!((|c: CornerPosCoord| c.index() < 2).is_goal(other));
+    }
+
+    #[test]
+    fn parallel_solution_matches_solution_iter() {
+        use G1CubeTurn::*;
+
+        let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let eslice_table: BasicMoveTable<ESliceEdgePosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let move_table =
+            CompositeMoveTable::<CornerPosAndESliceCoord, _, _>::new(&cp_table, &eslice_table);
+        let prune_table = FullPruneTable::create(&move_table, CornerPosAndESliceCoord::default());
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let control = SearchControl::default();
+
+        let scrambles: &[&[G1CubeTurn]] = &[
+            &[],
+            &[R2],
+            &[R2, U],
+            &[R2, U, F2, D, L2, UP],
+            &[B2, D2, R2, U, F2, DP, L2, U2, B2, R2],
+        ];
+        for scramble in scrambles {
+            let perm = apply(Cube3Perm::identity(), scramble);
+            let target = [CornerPosAndESliceCoord::default()];
+
+            let expected = SolutionIter::new(&move_table, &prune_table, target[0], &perm).next();
+            assert_eq!(
+                solve_parallel(
+                    &move_table,
+                    &prune_table,
+                    &target,
+                    &perm,
+                    &control,
+                    Some(&pool)
+                )
+                .ok(),
+                expected
+            );
+            assert_eq!(
+                solve_parallel(&move_table, &prune_table, &target, &perm, &control, None).ok(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn searches_stop_at_limits() {
+        use G1CubeTurn::*;
+
+        let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let eslice_table: BasicMoveTable<ESliceEdgePosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let move_table =
+            CompositeMoveTable::<CornerPosAndESliceCoord, _, _>::new(&cp_table, &eslice_table);
+        // Without pruning, searches take a lot of nodes
+        let prune_table = ZeroPruneTable::default();
+        let target = CornerPosAndESliceCoord::default();
+        let perm = apply(Cube3Perm::identity(), &[R2, U, F2, D, L2, UP, B2]);
+
+        let cancelled = SearchControl::default();
+        cancelled.cancel.cancel();
+        let controls = [
+            (
+                SearchControl {
+                    max_depth: Some(3),
+                    ..Default::default()
+                },
+                AbortReason::MaxDepth,
+            ),
+            (
+                SearchControl {
+                    max_nodes: Some(5000),
+                    ..Default::default()
+                },
+                AbortReason::MaxNodes,
+            ),
+            (
+                SearchControl {
+                    deadline: Some(std::time::Instant::now()),
+                    ..Default::default()
+                },
+                AbortReason::Deadline,
+            ),
+            (cancelled, AbortReason::Cancelled),
+        ];
+
+        for (control, reason) in controls.iter() {
+            let result = solve_cube(&move_table, &prune_table, &perm, target, control);
+            assert_eq!(result.map_err(|aborted| aborted.reason), Err(*reason));
+
+            let result = solve_parallel(&move_table, &prune_table, &[target], &perm, control, None);
+            assert_eq!(result.map_err(|aborted| aborted.reason), Err(*reason));
+        }
+
+        let mut solutions = SolutionIter::new(&move_table, &prune_table, target, &perm)
+            .with_control(controls[0].0.clone());
+        assert_eq!(solutions.next(), None);
+        let aborted = solutions.aborted().unwrap();
+        assert_eq!(aborted.reason, AbortReason::MaxDepth);
+        assert_eq!(aborted.depth, 4);
+        // The depth over the limit is not started
+        assert_eq!(solutions.stats().depths.last().unwrap().depth, 3);
+    }
+
+    #[test]
+    fn solved_state_is_within_depth_limit() {
+        let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let prune_table = FullPruneTable::create(&cp_table, CornerPosCoord::default());
+        let control = SearchControl {
+            max_depth: Some(0),
+            ..Default::default()
+        };
+
+        let mut solutions = SolutionIter::new(
+            &cp_table,
+            &prune_table,
+            CornerPosCoord::default(),
+            Cube3Perm::identity(),
+        )
+        .with_control(control);
+        assert_eq!(solutions.next(), Some(Vec::new()));
+        assert_eq!(solutions.aborted(), None);
+        assert_eq!(solutions.next(), None);
+        assert_eq!(solutions.aborted().unwrap().reason, AbortReason::MaxDepth);
+    }
+
+    #[test]
+    fn unreachable_target_exhausts_search() {
+        use crate::cube::cube3::coord::CornerOrientCoord;
+        use crate::cube::cube3::CubeTurn;
+
+        // G1 moves don't twist the corners, so a twisted cube can't be solved with them
+        let co_table: BasicMoveTable<CornerOrientCoord, G1CubeTurn> = BasicMoveTable::create();
+        let prune_table = FullPruneTable::create(&co_table, CornerOrientCoord::default());
+        let target = CornerOrientCoord::default();
+        let perm = CubeTurn::R.permutation().clone();
+
+        let mut solutions = SolutionIter::new(&co_table, &prune_table, target, &perm);
+        assert_eq!(solutions.next(), None);
+        assert_eq!(solutions.aborted().unwrap().reason, AbortReason::Exhausted);
+
+        let control = SearchControl::default();
+        let result = solve_parallel(&co_table, &prune_table, &[target], &perm, &control, None);
+        assert_eq!(
+            result.map_err(|aborted| aborted.reason),
+            Err(AbortReason::Exhausted)
+        );
+    }
+
+    #[test]
+    fn stats_count_search_work() {
+        use G1CubeTurn::*;
+
+        let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let prune_table = FullPruneTable::create(&cp_table, CornerPosCoord::default());
+        let perm = apply(Cube3Perm::identity(), &[R2, U, F2, D]);
+
+        let (solution, stats) = solve_cube_with_stats(
+            &cp_table,
+            &prune_table,
+            &perm,
+            CornerPosCoord::default(),
+            &SearchControl::default(),
+        );
+        let solution = solution.unwrap();
+
+        let last = stats.depths.last().unwrap();
+        assert_eq!(last.depth, solution.len() as u32);
+        assert!(stats.depths.windows(2).all(|d| d[0].depth < d[1].depth));
+        for depth in stats.depths.iter() {
+            assert_eq!(depth.lookups, depth.expanded + depth.pruned);
+        }
+
+        // Without a real pruning table, every depth is searched and more nodes are expanded
+        let zero_prune_table = ZeroPruneTable::default();
+        let (_, zero_stats) = solve_cube_with_stats(
+            &cp_table,
+            &zero_prune_table,
+            &perm,
+            CornerPosCoord::default(),
+            &SearchControl::default(),
+        );
+        assert_eq!(
+            zero_stats
+                .depths
+                .iter()
+                .map(|d| d.depth)
+                .collect::<Vec<_>>(),
+            (1..=solution.len() as u32).collect::<Vec<_>>()
+        );
+        assert!(zero_stats.expanded() > stats.expanded());
+    }
+
+    #[test]
+    fn inverse_lookups_prune_without_changing_solutions() {
+        use crate::cube::cube2::coord::{Corner7Coord, CornerOrient7Coord, CornerPos7Coord};
+        use crate::cube::cube2::{Cube2Perm, UrfTurn, UrfTurn::*};
+
+        let orient_table: BasicMoveTable<CornerOrient7Coord, UrfTurn> = BasicMoveTable::create();
+        let pos_table: BasicMoveTable<CornerPos7Coord, UrfTurn> = BasicMoveTable::create();
+        let move_table = CompositeMoveTable::<Corner7Coord, _, _>::new(&orient_table, &pos_table);
+        let orient_prune_table = FullPruneTable::create(&orient_table, Default::default());
+        let pos_prune_table = FullPruneTable::create(&pos_table, Default::default());
+        let prune_table = CompositePruneTable::new(&orient_prune_table, &pos_prune_table);
+
+        let perm = [R, U2, FP, R, F2, U, RP, F, U2]
+            .iter()
+            .fold(Cube2Perm::identity().clone(), |perm, m| {
+                perm.sequence(m.permutation())
+            });
+        let target = Corner7Coord::default();
+
+        let mut solutions = SolutionIter::new(&move_table, &prune_table, target, &perm);
+        let inverse_table = InverseMoveTable::create();
+        let mut inverse_solutions = SolutionIter::new(&move_table, &prune_table, target, &perm)
+            .with_inverse_lookups(&inverse_table);
+        for _ in 0..5 {
+            assert_eq!(inverse_solutions.next(), solutions.next());
+        }
+        assert!(inverse_solutions.stats().expanded() < solutions.stats().expanded());
+    }
+
+    #[test]
+    #[should_panic(expected = "solved state")]
+    fn inverse_lookups_need_solved_target() {
+        let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let inverse_table = InverseMoveTable::create();
+        let target = CornerPosCoord::from_perm(G1CubeTurn::U.permutation());
+        let prune_table = FullPruneTable::create(&cp_table, target);
+
+        let _ = SolutionIter::new(&cp_table, &prune_table, target, Cube3Perm::identity())
+            .with_inverse_lookups(&inverse_table);
+    }
+
+    #[test]
+    fn puzzle_solutions_are_shortest() {
+        use crate::cube::cube2::coord::{Corner7Coord, CornerOrient7Coord, CornerPos7Coord};
+        use crate::cube::cube2::{Cube2Perm, UrfTurn, UrfTurn::*};
+
+        let orient_table: BasicMoveTable<CornerOrient7Coord, UrfTurn> = BasicMoveTable::create();
+        let orient_prune_table = FullPruneTable::create(&orient_table, Default::default());
+
+        // Exact table for the whole puzzle to compare against
+        let pos_table: BasicMoveTable<CornerPos7Coord, UrfTurn> = BasicMoveTable::create();
+        let pos_prune_table = FullPruneTable::create(&pos_table, Default::default());
+        let move_table = CompositeMoveTable::<Corner7Coord, _, _>::new(&orient_table, &pos_table);
+        let prune_table = CompositePruneTable::new(&orient_prune_table, &pos_prune_table);
+
+        let scrambles: &[&[UrfTurn]] = &[&[], &[R], &[R, U2, FP, R], &[F, R, U2, FP, R, U]];
+        for scramble in scrambles {
+            let perm = scramble
+                .iter()
+                .fold(Cube2Perm::identity().clone(), |perm, m| {
+                    perm.sequence(m.permutation())
+                });
+
+            // Only orientation is used in the search, but the whole puzzle is solved
+            let solution = solve_puzzle(
+                &orient_table,
+                &orient_prune_table,
+                &perm,
+                &SearchControl::default(),
+            )
+            .unwrap();
+            assert!(solution
+                .iter()
+                .fold(perm.clone(), |perm, m| perm.sequence(m.permutation()))
+                .is_identity());
+
+            let expected = SolutionIter::new(&move_table, &prune_table, Default::default(), &perm)
+                .next()
+                .unwrap();
+            assert_eq!(solution.len(), expected.len());
+        }
+    }
+
+    #[test]
+    fn solution_iter_stops_at_closure_goal() {
+        use G1CubeTurn::*;
+
+        let cp_table: BasicMoveTable<CornerPosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let eslice_table: BasicMoveTable<ESliceEdgePosCoord, G1CubeTurn> = BasicMoveTable::create();
+        let move_table =
+            CompositeMoveTable::<CornerPosAndESliceCoord, _, _>::new(&cp_table, &eslice_table);
+        // Solving just the corners doesn't depend on the E slice edges
+        let corner_prune_table = FullPruneTable::create(&cp_table, CornerPosCoord::default());
+        let zero_prune_table = ZeroPruneTable::default();
+        let prune_table = CompositePruneTable::new(&corner_prune_table, &zero_prune_table);
+
+        let perm = apply(Cube3Perm::identity(), &[R2, U, F2, D]);
+        let goal = |coord: CornerPosAndESliceCoord| coord.corners() == CornerPosCoord::default();
+
+        let solutions = SolutionIter::with_goal(&move_table, &prune_table, goal, &perm)
+            .take(5)
+            .collect::<Vec<_>>();
+        assert_eq!(solutions[0].len(), 4);
+        for solution in solutions {
+            assert!(goal(CornerPosAndESliceCoord::from_perm(&apply(
+                &perm, &solution
+            ))));
+        }
+    }
+}
+
+/*
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cube::coord::Corner7Coord;
+    use crate::cube::moves::UrfTurn;
+    use crate::cube::primitives;
+    use crate::move_table::test::CORNER_MOVE_TABLE;
+    use crate::prune_table::test::CORNER_PRUNE_TABLE;
+
+    fn solve(perm: &PuzzlePerm) -> Option<Vec<UrfTurn>> {
+        solve_cube(
+            &*CORNER_MOVE_TABLE,
+            &*CORNER_PRUNE_TABLE,
+            perm,
+            Corner7Coord::default(),
+        )
+    }
+
+    #[test]
+    fn solves_solved_cube() {
+        assert_eq!(solve(primitives::identity()), Some(Vec::new()));
+    }
+
+    // TODO Make these more robust?
+
+    #[test]
+    fn solves_with_one_move() {
+        assert_eq!(solve(primitives::u()), Some(vec![UrfTurn::UP]));
+    }
+
+    #[test]
+    fn solves_with_two_moves() {
+        let perm = primitives::u() + primitives::r_prime();
+        assert_eq!(solve(&perm), Some(vec![UrfTurn::R, UrfTurn::UP]));
+    }
+}
+*/
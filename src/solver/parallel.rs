@@ -0,0 +1,262 @@
+//! IDA* search split across multiple threads.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+use crate::coord::Coord;
+use crate::move_table::MoveTable;
+use crate::prune_table::{PruneTable, UNREACHABLE};
+use crate::puzzle::PuzzleMove;
+use crate::util::{self, IntoEnumIterator};
+
+use super::{AbortReason, Aborted, Goal, SearchControl};
+
+/// Number of moves from the root which are expanded before splitting the search between threads.
+const SPLIT_DEPTH: usize = 2;
+
+/// Part of the search tree, starting after some moves from the root.
+struct Subtree<C, M> {
+    coord: C,
+    bound: u32,
+    moves: Vec<M>,
+}
+
+/// State shared between the threads for one iteration of IDA*.
+struct Search<'a, MT, PT, G> {
+    move_table: &'a MT,
+    prune_table: &'a PT,
+    goal: &'a G,
+    control: &'a SearchControl,
+    max_depth: u32,
+    /// Smallest estimated depth greater than `max_depth` which was found
+    next_max_depth: AtomicU32,
+    /// Nodes visited in all iterations, counted in batches
+    nodes: &'a AtomicU64,
+    /// Set when any thread hits one of the limits
+    abort: OnceLock<AbortReason>,
+}
+
+impl<'a, MT, PT, G> Search<'a, MT, PT, G>
+where
+    MT: MoveTable,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move>,
+    G: Goal<MT::Coord>,
+{
+    /// Stop all of the threads if a limit was hit.
+    fn check_limits<F>(&self, check: F)
+    where
+        F: FnOnce(&SearchControl) -> Result<(), AbortReason>,
+    {
+        if let Err(reason) = check(self.control) {
+            // Only the first reason is kept
+            let _ = self.abort.set(reason);
+        }
+    }
+
+    /// Search a subtree, adding the nodes visited to the shared count.
+    fn search_subtree(&self, subtree: &Subtree<MT::Coord, MT::Move>) -> Option<Vec<MT::Move>> {
+        // Subtrees can be small, so check before each of them as well as during the search
+        self.check_limits(|control| control.check_interrupt());
+
+        let mut path = subtree.moves.clone();
+        let mut local_nodes = 0;
+        let found = self.search(subtree.coord, subtree.bound, &mut path, &mut local_nodes);
+
+        let nodes = self.nodes.fetch_add(local_nodes, Ordering::Relaxed) + local_nodes;
+        self.check_limits(|control| control.check_nodes(nodes));
+
+        if found {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Depth-first search for a solution of length `max_depth`, leaving it in `path`.
+    ///
+    /// Nodes are counted in `local_nodes` until there are enough for a batch.
+    fn search(
+        &self,
+        coord: MT::Coord,
+        bound: u32,
+        path: &mut Vec<MT::Move>,
+        local_nodes: &mut u64,
+    ) -> bool {
+        *local_nodes += 1;
+        if *local_nodes == SearchControl::CHECK_INTERVAL {
+            *local_nodes = 0;
+            let nodes = self
+                .nodes
+                .fetch_add(SearchControl::CHECK_INTERVAL, Ordering::Relaxed)
+                + SearchControl::CHECK_INTERVAL;
+            self.check_limits(|control| {
+                control.check_nodes(nodes)?;
+                control.check_interrupt()
+            });
+        }
+
+        if self.abort.get().is_some() {
+            return false;
+        }
+
+        let depth = path.len() as u32;
+        let heuristic_depth = bound.saturating_add(depth);
+        if heuristic_depth > self.max_depth {
+            // States which can't reach the goal won't have a solution at any depth
+            if bound < UNREACHABLE {
+                self.next_max_depth
+                    .fetch_min(heuristic_depth, Ordering::Relaxed);
+            }
+            return false;
+        }
+
+        // Shorter solutions were found in earlier iterations
+        if depth == self.max_depth {
+            if self.goal.is_goal(coord) {
+                return true;
+            }
+
+            // There may be solutions after this one
+            self.next_max_depth
+                .fetch_min(self.max_depth + 1, Ordering::Relaxed);
+            return false;
+        }
+
+        for m in MT::Move::iter() {
+            // If the new move combines with the old one, there is another (single) move which is
+            // their combination
+            if path.last().is_some_and(|last| last.combines_with(&m)) {
+                continue;
+            }
+
+            let new_coord = self.move_table.get_move(coord, m);
+            let new_bound = self.prune_table.get_min_moves_from(new_coord, bound);
+
+            path.push(m);
+            if self.search(new_coord, new_bound, path, local_nodes) {
+                return true;
+            }
+            path.pop();
+        }
+
+        false
+    }
+}
+
+/// Find a shortest solution, splitting the search between threads.
+///
+/// The first few moves from the root are expanded into subtrees which are searched in parallel
+/// with the same move and pruning tables. Threads which run out of subtrees steal them from busy
+/// ones. Each depth is finished before moving on to the next one, so the solution is the same as
+/// the first one from `SolutionIter`.
+///
+/// The work is done in `pool` if given, or in the global thread pool otherwise.
+///
+/// If the search is stopped by one of the limits in `control`, a solution may still be returned if
+/// one was found, but it may not be the same as the one from `SolutionIter`.
+pub fn solve_parallel<MT, PT, G>(
+    move_table: &MT,
+    prune_table: &PT,
+    goal: &G,
+    perm: &MT::Puzzle,
+    control: &SearchControl,
+    pool: Option<&ThreadPool>,
+) -> Result<Vec<MT::Move>, Aborted>
+where
+    MT: MoveTable + Sync,
+    PT: PruneTable<Puzzle = MT::Puzzle, Coord = MT::Coord, Move = MT::Move> + Sync,
+    G: Goal<MT::Coord> + Sync,
+    MT::Coord: Send + Sync,
+    MT::Move: Send + Sync,
+{
+    let init_coord = MT::Coord::from_perm(perm);
+    let mut subtrees = vec![Subtree {
+        coord: init_coord,
+        bound: prune_table.get_min_moves(init_coord),
+        moves: Vec::new(),
+    }];
+
+    let nodes = AtomicU64::new(0);
+    let aborted = |reason, depth| Aborted {
+        reason,
+        depth,
+        nodes: nodes.load(Ordering::Relaxed),
+    };
+
+    // Expand the first moves, one depth at a time so that short solutions are found in order
+    for depth in 0..=SPLIT_DEPTH {
+        if let Some(subtree) = subtrees.iter().find(|s| goal.is_goal(s.coord)) {
+            return Ok(subtree.moves.clone());
+        }
+
+        if depth == SPLIT_DEPTH {
+            break;
+        }
+
+        control
+            .check_depth(depth as u32 + 1)
+            .map_err(|reason| aborted(reason, depth as u32 + 1))?;
+        nodes.fetch_add(subtrees.len() as u64, Ordering::Relaxed);
+
+        subtrees = subtrees
+            .iter()
+            .flat_map(|subtree| {
+                MT::Move::iter()
+                    .filter(move |m| !subtree.moves.last().is_some_and(|l| l.combines_with(m)))
+                    .map(move |m| {
+                        let coord = move_table.get_move(subtree.coord, m);
+                        let mut moves = subtree.moves.clone();
+                        moves.push(m);
+                        Subtree {
+                            coord,
+                            bound: prune_table.get_min_moves_from(coord, subtree.bound),
+                            moves,
+                        }
+                    })
+            })
+            .collect();
+    }
+
+    let mut max_depth = SPLIT_DEPTH as u32 + 1;
+    loop {
+        debug!("Searching at depth {}", max_depth);
+        control
+            .check_depth(max_depth)
+            .map_err(|reason| aborted(reason, max_depth))?;
+
+        let search = Search {
+            move_table,
+            prune_table,
+            goal,
+            control,
+            max_depth,
+            next_max_depth: AtomicU32::new(u32::MAX),
+            nodes: &nodes,
+            abort: OnceLock::new(),
+        };
+
+        // Taking the first one keeps the result the same as the sequential search
+        let solution = util::install(pool, || {
+            subtrees
+                .par_iter()
+                .find_map_first(|subtree| search.search_subtree(subtree))
+        });
+        if let Some(solution) = solution {
+            return Ok(solution);
+        }
+
+        if let Some(&reason) = search.abort.get() {
+            return Err(aborted(reason, max_depth));
+        }
+
+        let next_max_depth = search.next_max_depth.into_inner();
+        if next_max_depth == u32::MAX {
+            // Only unreachable states were pruned
+            return Err(aborted(AbortReason::Exhausted, max_depth));
+        }
+        max_depth = next_max_depth;
+    }
+}
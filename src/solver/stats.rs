@@ -0,0 +1,75 @@
+//! Counts of the work done by a search.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Work done in one iteration of IDA*.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DepthStats {
+    /// Maximum solution length searched in this iteration.
+    pub depth: u32,
+
+    /// Nodes added to the search path, whose children are then searched.
+    pub expanded: u64,
+
+    /// Nodes which were not searched because the pruning table showed they were too far from the
+    /// goal.
+    pub pruned: u64,
+
+    /// Lookups in the move table.
+    pub lookups: u64,
+
+    /// Time spent searching, not counting time between solutions being returned.
+    pub time: Duration,
+}
+
+impl fmt::Display for DepthStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "depth {}: {} expanded, {} pruned, {} lookups in {:?}",
+            self.depth, self.expanded, self.pruned, self.lookups, self.time
+        )
+    }
+}
+
+/// Work done by a search, for each depth searched.
+///
+/// Depths are only included if they were searched. They can be skipped when the pruning table
+/// shows that there are no solutions of that length.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    pub depths: Vec<DepthStats>,
+}
+
+impl SearchStats {
+    pub fn expanded(&self) -> u64 {
+        self.depths.iter().map(|d| d.expanded).sum()
+    }
+
+    pub fn pruned(&self) -> u64 {
+        self.depths.iter().map(|d| d.pruned).sum()
+    }
+
+    pub fn lookups(&self) -> u64 {
+        self.depths.iter().map(|d| d.lookups).sum()
+    }
+
+    pub fn time(&self) -> Duration {
+        self.depths.iter().map(|d| d.time).sum()
+    }
+
+    /// Stats for the depth currently being searched.
+    pub(crate) fn current_mut(&mut self) -> &mut DepthStats {
+        self.depths
+            .last_mut()
+            .expect("search stats used before starting a depth")
+    }
+
+    /// Log the stats for each depth.
+    pub fn log(&self) {
+        for depth in self.depths.iter() {
+            info!("Search {}", depth);
+        }
+    }
+}
@@ -1,7 +1,11 @@
+use std::io::{self, Read, Write};
 use std::marker::PhantomData;
 
+use num_traits::FromPrimitive;
+
 use crate::coord::Coord;
 use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::table_io::{self, Header, LoadError};
 use crate::util::{EnumCount, EnumIndex, IntoEnumIterator};
 
 /// A subgorup of puzzle permutations which separate a puzzle permutation into a set of equivalence
@@ -19,6 +23,9 @@ pub trait SymCoord<P: PuzzlePerm>: EnumIndex {
     type Symmetry: Symmetry<Puzzle = P>;
     type EquivClass: EnumIndex;
 
+    /// Name identifying the coordinate in saved tables, like `Coord::NAME`.
+    const NAME: &'static str;
+
     fn from_sym_and_class(sym: Self::Symmetry, equiv_class: Self::EquivClass) -> Self;
 
     fn symmetry(&self) -> Self::Symmetry;
@@ -42,11 +49,15 @@ macro_rules! make_symmetry_coord {
             $v struct $newtype($inner): (($count) * <$sym>::COUNT);
         }
 
-        impl $crate::symmetry::SymCoord<<$sym>::Puzzle> for $newtype {
+        impl $crate::symmetry::SymCoord<<$sym as $crate::puzzle::PuzzleMove>::Puzzle>
+            for $newtype
+        {
             type BaseCoord = $coord;
             type Symmetry = $sym;
             type EquivClass = $equivclass;
 
+            const NAME: &'static str = stringify!($newtype);
+
             fn from_sym_and_class(sym: $sym, equiv_class: Self::EquivClass) -> Self {
                 use $crate::util::{EnumIndex, EnumCount};
                 Self((equiv_class.index() * <$sym>::COUNT + sym.index()) as $inner)
@@ -75,94 +86,110 @@ struct RepresentantEntry<C> {
 
 /// Table of equivalent class representants under a symmetry.
 ///
-/// This is used for creating and using symmetry-reduced coordinates.
+/// This is used for creating and using symmetry-reduced coordinates. Along with the representants,
+/// it holds the symmetry-reduced coordinate of every regular coordinate, so converting between them
+/// is just a lookup.
 #[derive(Clone, Debug)]
 pub struct RepresentantTable<P: PuzzlePerm, S: SymCoord<P>> {
     table: Box<[RepresentantEntry<S::BaseCoord>]>,
-    _sym: PhantomData<S>,
+    /// Symmetry-reduced coordinate for each regular coordinate
+    sym_coords: Box<[S]>,
 }
 
-/// Checks whether a permutation is in an equivalence class.
-fn sym_equivalent_perm<S: Symmetry, C: Coord<S::Puzzle>>(a: C, b_perm: &S::Puzzle) -> Option<S> {
-    S::iter().find(|sym| {
-        let conj_perm = sym
-            .permutation()
-            .sequence(b_perm)
-            .sequence(sym.invert().permutation());
-        C::from_perm(&conj_perm) == a
-    })
+/// Conjugates a permutation by a symmetry (i.e. `sym * perm * sym^-1`).
+pub(crate) fn conjugate<S: Symmetry>(sym: &S, perm: &S::Puzzle) -> S::Puzzle {
+    sym.permutation()
+        .sequence(perm)
+        .sequence(sym.invert().permutation())
 }
 
-/// Checks whether 2 coordinate values are equivalent under a symmetry.
-fn sym_equivalent<S: Symmetry, C: Coord<S::Puzzle>>(a: C, b: C) -> Option<S> {
-    sym_equivalent_perm(a, &b.into_perm())
+/// Fill in the symmetry-reduced coordinates for an equivalence class, returning the bitmask of
+/// symmetries which leave the representant unchanged.
+///
+/// Every coordinate in the class is a conjugate of the representant, so this only has to conjugate
+/// the representant by each symmetry.
+fn fill_class<P: PuzzlePerm, S: SymCoord<P>>(
+    sym_coords: &mut [Option<S>],
+    representant: S::BaseCoord,
+    equiv_class: S::EquivClass,
+) -> u64 {
+    let perm = representant.into_perm();
+    let mut bitmask = 0;
+    for sym in S::Symmetry::iter() {
+        let coord = S::BaseCoord::from_perm(&conjugate(&sym, &perm));
+        if coord == representant {
+            bitmask |= 1 << sym.index();
+        }
+
+        // The inverse takes the conjugate back to the representant
+        let entry = &mut sym_coords[coord.index()];
+        if entry.is_none() {
+            *entry = Some(S::from_sym_and_class(sym.invert(), equiv_class));
+        }
+    }
+
+    bitmask
 }
 
 impl<P: PuzzlePerm, S: SymCoord<P>> RepresentantTable<P, S> {
     pub fn create() -> Self {
         assert!(
-            S::COUNT <= 64,
+            S::Symmetry::COUNT <= 64,
             "Cannot calculate representant table for symmetries with more than 64 elements"
         );
 
-        // Get a list of all coordinate values
-        // TODO Use an invalid value instead of Option to save space?
-        let mut coord_values = S::BaseCoord::iter()
-            .map(|c| Some(c))
-            .collect::<Vec<_>>()
-            .into_boxed_slice();
-
-        // Will be slightly larger, but this is a good starting point
-        let mut table = Vec::with_capacity(S::BaseCoord::COUNT / S::COUNT);
-
-        for i in 0..S::BaseCoord::COUNT {
-            if let Some(repr_coord) = coord_values[i] {
-                let mut entry = RepresentantEntry {
-                    coord: repr_coord,
-                    // Start with all symmetries, and narrow it down when a distinct permutation is
-                    // found to be equal to the representant under some symmetry
-                    symmetry_bitmask: std::u64::MAX,
-                };
-
-                // Look for coordinates which belong to the same equivalence class (i.e. are equal
-                // to the representant under some symmety)
-                // Since this only looks at later coordiantes, the representant is always the
-                // smallest
-                for j in i + 1..S::BaseCoord::COUNT {
-                    if let Some(coord) = coord_values[j] {
-                        // Remove the coordinate if it belongs to this equivalence class
-                        if let Some(sym) =
-                            sym_equivalent::<S::Symmetry, S::BaseCoord>(repr_coord, coord)
-                        {
-                            coord_values[j] = None;
-                            // Mask out the symmetry
-                            entry.symmetry_bitmask &= !(1 << sym.index());
-                            break;
-                        }
-                    }
-                }
-
-                table.push(entry);
+        let mut sym_coords = vec![None; S::BaseCoord::COUNT];
+        let mut classes = S::EquivClass::iter();
+        let mut table = Vec::with_capacity(S::EquivClass::COUNT);
+
+        // Coordinates are visited in order, so the representant is always the smallest in its
+        // class
+        for coord in S::BaseCoord::iter() {
+            if sym_coords[coord.index()].is_none() {
+                let equiv_class = classes
+                    .next()
+                    .expect("More equivalence classes than the symmetry coordinate allows");
+                let symmetry_bitmask = fill_class(&mut sym_coords, coord, equiv_class);
+                table.push(RepresentantEntry {
+                    coord,
+                    symmetry_bitmask,
+                });
             }
         }
 
         Self {
             table: table.into_boxed_slice(),
-            _sym: PhantomData::default(),
+            sym_coords: sym_coords
+                .into_iter()
+                .map(|sym_coord| sym_coord.unwrap()) // All classes have been filled in
+                .collect(),
         }
     }
 
     /// Find the equivalence class of a coordinate and the symmetry which takes it to the
     /// representant of the class.
     pub fn get_equiv_class(&self, coord: S::BaseCoord) -> (S::Symmetry, S::EquivClass) {
-        let perm = coord.into_perm();
-        self.table
-            .iter()
-            .zip(S::EquivClass::iter())
-            .find_map(|(representant, index)| {
-                sym_equivalent_perm(representant.coord, &perm).map(|sym| (sym, index))
-            })
-            .unwrap() // Will always exist
+        let sym_coord = self.get_sym_coord(coord);
+        (sym_coord.symmetry(), sym_coord.equiv_class())
+    }
+
+    /// Find the symmetry-reduced coordinate of a coordinate.
+    pub fn get_sym_coord(&self, coord: S::BaseCoord) -> S {
+        self.sym_coords[coord.index()]
+    }
+
+    /// Find the regular coordinate which corresponds to a symmetry-reduced coordinate.
+    ///
+    /// This is the inverse of `get_sym_coord`.
+    pub fn get_base_coord(&self, sym_coord: S) -> S::BaseCoord {
+        let sym = sym_coord.symmetry();
+        let repr_perm = self.get_representant(sym_coord.equiv_class()).into_perm();
+        let perm = sym
+            .invert()
+            .permutation()
+            .sequence(&repr_perm)
+            .sequence(sym.permutation());
+        S::BaseCoord::from_perm(&perm)
     }
 
     /// Get the representant for an equivalence class
@@ -172,29 +199,130 @@ impl<P: PuzzlePerm, S: SymCoord<P>> RepresentantTable<P, S> {
 
     /// Returns the class symmetries of an equivalence class.
     ///
-    /// These are the symmetries which have no effect when used to conjugate an element in this
-    /// equivalence class.
-    pub fn get_class_symmetries(&self, equiv_class: S::EquivClass) -> impl Iterator<Item = S> {
-        let mut bitmask = self.table[equiv_class.index()].symmetry_bitmask;
-        let mut iter = S::iter();
-        std::iter::from_fn(move || {
-            if bitmask > 0 {
-                // Trailing zeros is how many to skip until the next one
-                let n = bitmask.trailing_zeros();
-                bitmask >>= n + 1;
-                // Consumes n and returns and consumes the next
-                iter.nth(n as usize)
-            } else {
-                None
-            }
-        })
+    /// These are the symmetries which have no effect when used to conjugate the representant of
+    /// this equivalence class (i.e. its stabilizer). The identity is always included.
+    pub fn get_class_symmetries(
+        &self,
+        equiv_class: S::EquivClass,
+    ) -> impl Iterator<Item = S::Symmetry> {
+        let bitmask = self.table[equiv_class.index()].symmetry_bitmask;
+        S::Symmetry::iter().filter(move |sym| bitmask & (1 << sym.index()) != 0)
     }
 
     pub fn len(&self) -> usize {
         self.table.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = S::BaseCoord> + 'a {
         self.table.iter().map(|e| e.coord)
     }
+
+    /// Write the table in the format described in `table_io`.
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut entries = Vec::with_capacity(self.table.len() * 12);
+        for entry in self.table.iter() {
+            entries.extend_from_slice(&(entry.coord.index() as u32).to_le_bytes());
+            entries.extend_from_slice(&entry.symmetry_bitmask.to_le_bytes());
+        }
+
+        let header = Header::representant::<P, S>(self.table.len());
+        table_io::write_table(&mut writer, &header, &entries)
+    }
+
+    /// Read a table written by `save`.
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, LoadError>
+    where
+        S::BaseCoord: FromPrimitive,
+    {
+        let header = Header::representant::<P, S>(S::EquivClass::COUNT);
+        let entries = table_io::read_table(&mut reader, &header, 12)?;
+
+        let table = entries
+            .chunks_exact(12)
+            .enumerate()
+            .map(|(index, bytes)| {
+                let mut bitmask = [0; 8];
+                bitmask.copy_from_slice(&bytes[4..]);
+                Ok(RepresentantEntry {
+                    coord: table_io::decode_coord(bytes, index)?,
+                    symmetry_bitmask: u64::from_le_bytes(bitmask),
+                })
+            })
+            .collect::<Result<Vec<RepresentantEntry<S::BaseCoord>>, LoadError>>()?;
+
+        // Only the representants are saved, since the rest can be found from them quickly
+        let mut sym_coords = vec![None; S::BaseCoord::COUNT];
+        let mut coords = S::BaseCoord::iter();
+        for (index, (entry, equiv_class)) in table.iter().zip(S::EquivClass::iter()).enumerate() {
+            // Like in `create`, each representant has to be the smallest coordinate which is not
+            // in an earlier class
+            let next = coords.find(|coord| sym_coords[coord.index()].is_none());
+            if next != Some(entry.coord) {
+                return Err(LoadError::InvalidEntry(index));
+            }
+
+            let symmetry_bitmask = fill_class(&mut sym_coords, entry.coord, equiv_class);
+            if symmetry_bitmask != entry.symmetry_bitmask {
+                return Err(LoadError::InvalidEntry(index));
+            }
+        }
+
+        // Every coordinate has to be in one of the classes, otherwise there are entries missing
+        let sym_coords = sym_coords
+            .into_iter()
+            .collect::<Option<Box<[S]>>>()
+            .ok_or(LoadError::InvalidEntry(table.len()))?;
+
+        Ok(Self {
+            table: table.into_boxed_slice(),
+            sym_coords,
+        })
+    }
+}
+
+/// Table of the conjugates of a coordinate by each symmetry.
+///
+/// This only makes sense for coordinates which are preserved by the symmetries, i.e. conjugating
+/// 2 permutations with the same coordinate by a symmetry results in 2 permutations which also have
+/// the same coordinate. For example, corner orientation is preserved by the symmetries which fix
+/// the U-D axis.
+#[derive(Clone, Debug)]
+pub struct SymConjTable<C, S> {
+    table: Box<[C]>,
+    _sym: PhantomData<S>,
+}
+
+impl<P, C, S> SymConjTable<C, S>
+where
+    P: PuzzlePerm,
+    C: Coord<P>,
+    S: Symmetry<Puzzle = P>,
+{
+    pub fn create() -> Self {
+        let mut table = Vec::with_capacity(C::COUNT * S::COUNT);
+
+        for coord in C::iter() {
+            let perm = coord.into_perm();
+            for sym in S::iter() {
+                table.push(C::from_perm(&conjugate(&sym, &perm)));
+            }
+        }
+
+        Self {
+            table: table.into_boxed_slice(),
+            _sym: PhantomData,
+        }
+    }
+
+    /// Conjugate a coordinate by a symmetry.
+    ///
+    /// This uses the same convention as `RepresentantTable::get_equiv_class`, so the symmetry
+    /// returned from there can be used to transform other coordinates of the same permutation.
+    pub fn get_conj(&self, coord: C, sym: S) -> C {
+        self.table[coord.index() * S::COUNT + sym.index()]
+    }
 }
@@ -0,0 +1,553 @@
+//! Binary serialization for tables.
+//!
+//! Every table is written with a header describing its contents, so that loading data into the
+//! wrong kind of table (or a table for a different coordinate) fails instead of silently producing
+//! garbage. The header is laid out as follows, with all integers little-endian:
+//!
+//! | Field            | Encoding                         |
+//! |------------------|----------------------------------|
+//! | Magic            | `b"TWTB"`                        |
+//! | Format version   | `u32`                            |
+//! | Table kind       | `u8`                             |
+//! | Coordinate name  | `u16` length + UTF-8 name        |
+//! | Coordinate count | `u64`                            |
+//! | Move name        | `u16` length + UTF-8 name        |
+//! | Move count       | `u64`                            |
+//! | Entry count      | `u64`                            |
+//! | Checksum         | `u64` (FNV-1a of the entries)    |
+//!
+//! The entries follow the header. The names are `Coord::NAME` and `PuzzleMove::NAME` (or
+//! `SymCoord::NAME` and the symmetry's `PuzzleMove::NAME` for representant tables) rather than Rust
+//! type names, so renaming or moving a type doesn't invalidate the tables saved for it.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+use memmap2::Mmap;
+use num_traits::FromPrimitive;
+
+use crate::coord::Coord;
+use crate::puzzle::{PuzzleMove, PuzzlePerm};
+use crate::symmetry::SymCoord;
+use crate::util::EnumCount;
+
+const MAGIC: [u8; 4] = *b"TWTB";
+
+/// Version of the serialization format.
+///
+/// This is increased whenever the format, or the meaning of a saved table, changes.
+pub const FORMAT_VERSION: u32 = 3;
+
+/// Error when loading a table.
+#[derive(Debug)]
+pub enum LoadError {
+    /// Error reading the data.
+    Io(io::Error),
+
+    /// The data does not start with the magic bytes, so it is not a table.
+    BadMagic,
+
+    /// The table was saved with a different format version.
+    Version(u32),
+
+    /// A header field does not match the table being loaded.
+    Mismatch {
+        field: &'static str,
+        expected: String,
+        found: String,
+    },
+
+    /// The entries do not match the checksum in the header.
+    Checksum,
+
+    /// An entry is not a valid value.
+    InvalidEntry(usize),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "error reading table: {}", err),
+            LoadError::BadMagic => f.write_str("not a table file"),
+            LoadError::Version(version) => write!(
+                f,
+                "unsupported format version {} (expected {})",
+                version, FORMAT_VERSION
+            ),
+            LoadError::Mismatch {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "table {} mismatch: expected {}, found {}",
+                field, expected, found
+            ),
+            LoadError::Checksum => f.write_str("table checksum mismatch"),
+            LoadError::InvalidEntry(index) => write!(f, "invalid table entry at index {}", index),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TableKind {
+    Move = 0,
+    Prune = 1,
+    Representant = 2,
+    InverseMove = 3,
+}
+
+/// Header describing the contents of a table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Header {
+    kind: TableKind,
+    coord: String,
+    coord_count: u64,
+    moves: String,
+    move_count: u64,
+    len: u64,
+}
+
+impl Header {
+    /// Header for a table of `len` entries indexed by the coordinate `C` and moves `M`.
+    pub fn new<C, M>(kind: TableKind, len: usize) -> Self
+    where
+        C: Coord<M::Puzzle>,
+        M: PuzzleMove,
+    {
+        Self {
+            kind,
+            coord: C::NAME.to_owned(),
+            coord_count: C::COUNT as u64,
+            moves: M::NAME.to_owned(),
+            move_count: M::COUNT as u64,
+            len: len as u64,
+        }
+    }
+
+    /// Header for a representant table of `len` entries for the symmetry coordinate `S`.
+    ///
+    /// The symmetry takes the place of the moves.
+    pub fn representant<P: PuzzlePerm, S: SymCoord<P>>(len: usize) -> Self {
+        Self {
+            kind: TableKind::Representant,
+            coord: S::NAME.to_owned(),
+            coord_count: S::COUNT as u64,
+            moves: S::Symmetry::NAME.to_owned(),
+            move_count: S::Symmetry::COUNT as u64,
+            len: len as u64,
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn write_str<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    writer.write_all(&(s.len() as u16).to_le_bytes())?;
+    writer.write_all(s.as_bytes())
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut buf = [0; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_str<R: Read>(reader: &mut R) -> io::Result<String> {
+    let len = read_u16(reader)?;
+    let mut buf = vec![0; len as usize];
+    reader.read_exact(&mut buf)?;
+    // Invalid UTF-8 will never match the expected name anyway
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Checks that a header field has the expected value.
+fn check_field<T: PartialEq + ToString>(
+    field: &'static str,
+    expected: T,
+    found: T,
+) -> Result<(), LoadError> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(LoadError::Mismatch {
+            field,
+            expected: expected.to_string(),
+            found: found.to_string(),
+        })
+    }
+}
+
+/// Write a table header followed by its entries, already encoded as bytes.
+pub(crate) fn write_table<W: Write>(
+    writer: &mut W,
+    header: &Header,
+    entries: &[u8],
+) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&[header.kind as u8])?;
+    write_str(writer, &header.coord)?;
+    writer.write_all(&header.coord_count.to_le_bytes())?;
+    write_str(writer, &header.moves)?;
+    writer.write_all(&header.move_count.to_le_bytes())?;
+    writer.write_all(&header.len.to_le_bytes())?;
+    writer.write_all(&checksum(entries).to_le_bytes())?;
+    writer.write_all(entries)
+}
+
+/// Read a table header and check that it matches `expected`.
+///
+/// Returns the checksum of the entries.
+fn read_header<R: Read>(reader: &mut R, expected: &Header) -> Result<u64, LoadError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(LoadError::BadMagic);
+    }
+
+    let version = read_u32(reader)?;
+    if version != FORMAT_VERSION {
+        return Err(LoadError::Version(version));
+    }
+
+    check_field("kind", expected.kind as u8, read_u8(reader)?)?;
+    check_field("coordinate", &expected.coord, &read_str(reader)?)?;
+    check_field("coordinate count", expected.coord_count, read_u64(reader)?)?;
+    check_field("move", &expected.moves, &read_str(reader)?)?;
+    check_field("move count", expected.move_count, read_u64(reader)?)?;
+    check_field("length", expected.len, read_u64(reader)?)?;
+
+    Ok(read_u64(reader)?)
+}
+
+/// Read a table header and the entries following it.
+///
+/// The header must match `expected`. `entry_size` is the number of bytes in each entry.
+pub(crate) fn read_table<R: Read>(
+    reader: &mut R,
+    expected: &Header,
+    entry_size: usize,
+) -> Result<Vec<u8>, LoadError> {
+    let sum = read_header(reader, expected)?;
+
+    let mut entries = vec![0; expected.len as usize * entry_size];
+    reader.read_exact(&mut entries)?;
+
+    if checksum(&entries) != sum {
+        return Err(LoadError::Checksum);
+    }
+
+    Ok(entries)
+}
+
+/// Table entries in a memory-mapped file.
+///
+/// This can be shared between processes which map the same file, since the pages are never
+/// written to.
+pub struct MappedTable {
+    map: Mmap,
+    offset: usize,
+    len: usize,
+}
+
+impl AsRef<[u8]> for MappedTable {
+    fn as_ref(&self) -> &[u8] {
+        &self.map[self.offset..self.offset + self.len]
+    }
+}
+
+/// Memory-map a file containing a single table, with the same checks as `read_table`.
+///
+/// Note: The file must not be modified while it is mapped.
+pub(crate) fn map_table(
+    file: &File,
+    expected: &Header,
+    entry_size: usize,
+) -> Result<MappedTable, LoadError> {
+    let table = map_table_at(file, 0, expected, entry_size)?;
+
+    // The file has to be exactly the header and the entries, since nothing else could use the rest
+    if table.offset + table.len < table.map.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected data after the end of the table",
+        )
+        .into());
+    }
+
+    Ok(table)
+}
+
+/// Memory-map a table which starts `offset` bytes into a file, with the same checks as
+/// `read_table`.
+///
+/// This is for files with several tables written one after another. Anything before or after the
+/// table is ignored.
+///
+/// Note: The file must not be modified while it is mapped.
+pub(crate) fn map_table_at(
+    file: &File,
+    offset: u64,
+    expected: &Header,
+    entry_size: usize,
+) -> Result<MappedTable, LoadError> {
+    // Safety: The map is only ever read, and the caller is responsible for not modifying the file
+    let map = unsafe { Mmap::map(file)? };
+
+    let mut reader = usize::try_from(offset)
+        .ok()
+        .and_then(|offset| map.get(offset..))
+        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+    let sum = read_header(&mut reader, expected)?;
+
+    let offset = map.len() - reader.len();
+    let len = expected.len as usize * entry_size;
+    if reader.len() < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    let table = MappedTable { map, offset, len };
+    if checksum(table.as_ref()) != sum {
+        return Err(LoadError::Checksum);
+    }
+
+    Ok(table)
+}
+
+/// Decode a coordinate stored as a little-endian `u32`.
+pub(crate) fn decode_coord<C>(bytes: &[u8], index: usize) -> Result<C, LoadError>
+where
+    C: EnumCount + FromPrimitive,
+{
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    let value = u32::from_le_bytes(buf) as usize;
+
+    if value < C::COUNT {
+        C::from_usize(value).ok_or(LoadError::InvalidEntry(index))
+    } else {
+        Err(LoadError::InvalidEntry(index))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::cube::cube3::coord::{CornerOrientCoord, EdgeOrientCoord};
+    use crate::cube::cube3::CubeTurn;
+    use crate::move_table::{BasicMoveTable, MoveTable};
+    use crate::prune_table::{FullPruneTable, PruneTable};
+    use crate::util::IntoEnumIterator;
+
+    fn saved_move_table() -> Vec<u8> {
+        let table: BasicMoveTable<CornerOrientCoord, CubeTurn> = BasicMoveTable::create();
+        let mut buf = Vec::new();
+        table.save(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn move_table_round_trip() {
+        let table: BasicMoveTable<CornerOrientCoord, CubeTurn> = BasicMoveTable::create();
+        let mut buf = Vec::new();
+        table.save(&mut buf).unwrap();
+
+        let loaded: BasicMoveTable<CornerOrientCoord, CubeTurn> =
+            BasicMoveTable::load(buf.as_slice()).unwrap();
+        for coord in CornerOrientCoord::iter() {
+            for m in CubeTurn::iter() {
+                assert_eq!(loaded.get_move(coord, m), table.get_move(coord, m));
+            }
+        }
+    }
+
+    #[test]
+    fn prune_table_round_trip() {
+        let move_table: BasicMoveTable<CornerOrientCoord, CubeTurn> = BasicMoveTable::create();
+        let table = FullPruneTable::create(&move_table, CornerOrientCoord::default());
+        let mut buf = Vec::new();
+        table.save(&mut buf).unwrap();
+
+        let loaded: FullPruneTable<CornerOrientCoord, CubeTurn> =
+            FullPruneTable::load(buf.as_slice()).unwrap();
+        for coord in CornerOrientCoord::iter() {
+            assert_eq!(loaded.get_min_moves(coord), table.get_min_moves(coord));
+        }
+    }
+
+    #[test]
+    fn mapped_prune_table_matches_owned() {
+        let move_table: BasicMoveTable<CornerOrientCoord, CubeTurn> = BasicMoveTable::create();
+        let table = FullPruneTable::create(&move_table, CornerOrientCoord::default());
+
+        let path = std::env::temp_dir().join(format!("twisted-test-{}.tbl", std::process::id()));
+        table.save(File::create(&path).unwrap()).unwrap();
+        let file = File::open(&path).unwrap();
+
+        let mapped = FullPruneTable::<CornerOrientCoord, CubeTurn, _>::map(&file);
+        let wrong_coord = FullPruneTable::<EdgeOrientCoord, CubeTurn, _>::map(&file);
+        std::fs::remove_file(&path).unwrap();
+
+        let mapped = mapped.unwrap();
+        for coord in CornerOrientCoord::iter() {
+            assert_eq!(mapped.get_min_moves(coord), table.get_min_moves(coord));
+        }
+
+        assert!(matches!(wrong_coord, Err(LoadError::Mismatch { .. })));
+    }
+
+    #[test]
+    fn map_fails_for_trailing_data() {
+        let move_table: BasicMoveTable<CornerOrientCoord, CubeTurn> = BasicMoveTable::create();
+        let table = FullPruneTable::create(&move_table, CornerOrientCoord::default());
+        let mut buf = Vec::new();
+        table.save(&mut buf).unwrap();
+        buf.push(0);
+
+        let path =
+            std::env::temp_dir().join(format!("twisted-test-trailing-{}.tbl", std::process::id()));
+        std::fs::write(&path, &buf).unwrap();
+        let file = File::open(&path).unwrap();
+        let result = FullPruneTable::<CornerOrientCoord, CubeTurn, _>::map(&file);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(LoadError::Io(_))));
+    }
+
+    #[test]
+    fn map_at_finds_tables_in_a_shared_file() {
+        use std::io::Seek;
+
+        let co_moves: BasicMoveTable<CornerOrientCoord, CubeTurn> = BasicMoveTable::create();
+        let eo_moves: BasicMoveTable<EdgeOrientCoord, CubeTurn> = BasicMoveTable::create();
+        let co_table = FullPruneTable::create(&co_moves, CornerOrientCoord::default());
+        let eo_table = FullPruneTable::create(&eo_moves, EdgeOrientCoord::default());
+
+        let path =
+            std::env::temp_dir().join(format!("twisted-test-shared-{}.tbl", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        co_moves.save(&mut file).unwrap();
+        let co_offset = file.stream_position().unwrap();
+        co_table.save(&mut file).unwrap();
+        let eo_offset = file.stream_position().unwrap();
+        eo_table.save(&mut file).unwrap();
+        drop(file);
+
+        let file = File::open(&path).unwrap();
+        let co_mapped = FullPruneTable::<CornerOrientCoord, CubeTurn, _>::map_at(&file, co_offset);
+        let eo_mapped = FullPruneTable::<EdgeOrientCoord, CubeTurn, _>::map_at(&file, eo_offset);
+        let wrong_offset = FullPruneTable::<CornerOrientCoord, CubeTurn, _>::map_at(&file, 0);
+        let past_end = FullPruneTable::<CornerOrientCoord, CubeTurn, _>::map_at(&file, u64::MAX);
+        std::fs::remove_file(&path).unwrap();
+
+        let co_mapped = co_mapped.unwrap();
+        for coord in CornerOrientCoord::iter() {
+            assert_eq!(
+                co_mapped.get_min_moves(coord),
+                co_table.get_min_moves(coord)
+            );
+        }
+        let eo_mapped = eo_mapped.unwrap();
+        for coord in EdgeOrientCoord::iter() {
+            assert_eq!(
+                eo_mapped.get_min_moves(coord),
+                eo_table.get_min_moves(coord)
+            );
+        }
+
+        assert!(matches!(
+            wrong_offset,
+            Err(LoadError::Mismatch { field: "kind", .. })
+        ));
+        assert!(matches!(past_end, Err(LoadError::Io(_))));
+    }
+
+    #[test]
+    fn load_fails_for_different_coord() {
+        let buf = saved_move_table();
+        let result = BasicMoveTable::<EdgeOrientCoord, CubeTurn>::load(buf.as_slice());
+        // Names are the ones given by the types, not their paths
+        assert!(matches!(
+            result,
+            Err(LoadError::Mismatch {
+                field: "coordinate",
+                expected,
+                found,
+            }) if expected == "EdgeOrientCoord" && found == "CornerOrientCoord"
+        ));
+    }
+
+    #[test]
+    fn load_fails_for_different_kind() {
+        let buf = saved_move_table();
+        let result = FullPruneTable::<CornerOrientCoord, CubeTurn>::load(buf.as_slice());
+        assert!(matches!(
+            result,
+            Err(LoadError::Mismatch { field: "kind", .. })
+        ));
+    }
+
+    #[test]
+    fn load_fails_for_corrupted_entries() {
+        let mut buf = saved_move_table();
+        *buf.last_mut().unwrap() ^= 1;
+        let result = BasicMoveTable::<CornerOrientCoord, CubeTurn>::load(buf.as_slice());
+        assert!(matches!(result, Err(LoadError::Checksum)));
+    }
+
+    #[test]
+    fn load_fails_for_truncated_data() {
+        let buf = saved_move_table();
+        let result = BasicMoveTable::<CornerOrientCoord, CubeTurn>::load(&buf[..buf.len() - 1]);
+        assert!(matches!(result, Err(LoadError::Io(_))));
+    }
+
+    #[test]
+    fn load_fails_for_non_table() {
+        let result = BasicMoveTable::<CornerOrientCoord, CubeTurn>::load(&b"not a table"[..]);
+        assert!(matches!(result, Err(LoadError::BadMagic)));
+    }
+}
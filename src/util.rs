@@ -14,6 +14,21 @@ pub trait EnumIndex: 'static + Copy + Eq + EnumCount + IntoEnumIterator {
     fn index(&self) -> usize;
 }
 
+/// Run an operation in a thread pool, or in the current context if there isn't one.
+///
+/// This is for functions which take an optional pool to do parallel work in. Parallel iterators
+/// use the global pool when not run inside of another one.
+pub(crate) fn install<R, F>(pool: Option<&rayon::ThreadPool>, op: F) -> R
+where
+    R: Send,
+    F: FnOnce() -> R + Send,
+{
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
 pub fn rotate_left<T: Copy>(slice: &mut [T]) {
     let len = slice.len();
     let first = slice[0];